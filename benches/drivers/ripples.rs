@@ -1,12 +1,11 @@
 use spatial_led::{
     driver::{Data, Driver, Time},
-    Sled, SledResult, Vec2,
+    Rect, Sled, SledResult, Vec2,
 };
 
 use rand::Rng;
 
 use palette::rgb::Rgb;
-use std::ops::Range;
 
 const MAX_RIPPLES: usize = 12;
 const MAX_RADIUS: f32 = 12.0;
@@ -20,7 +19,7 @@ pub fn build_driver() -> Driver<Rgb> {
     driver.set_startup_commands(startup);
     driver.set_compute_commands(compute);
     driver.set_draw_commands(draw);
-    return driver;
+    driver
 }
 
 fn startup(sled: &mut Sled<Rgb>, data: &mut Data) -> SledResult {
@@ -33,8 +32,10 @@ fn startup(sled: &mut Sled<Rgb>, data: &mut Data) -> SledResult {
         radii.push(rand_init_radius());
     }
 
+    let spawn_area = Rect::new(sled_bounds.min * 1.25, sled_bounds.max * 1.25);
+    let mut rng = rand::thread_rng();
     for _ in 0..MAX_RIPPLES {
-        positions.push(rand_point_in_range(&sled_bounds));
+        positions.push(spawn_area.sample(|| rng.gen()));
     }
 
     data.set("radii", radii);
@@ -61,10 +62,12 @@ fn startup(sled: &mut Sled<Rgb>, data: &mut Data) -> SledResult {
 fn compute(sled: &Sled<Rgb>, data: &mut Data, time: &Time) -> SledResult {
     let delta = time.delta.as_secs_f32();
     let bounds = sled.domain();
+    let spawn_area = Rect::new(bounds.min * 1.25, bounds.max * 1.25);
+    let mut rng = rand::thread_rng();
     for i in 0..MAX_RIPPLES {
         let radius = data.get::<Vec<f32>>("radii")?[i];
         if radius > MAX_RADIUS {
-            let new_pos = rand_point_in_range(&bounds);
+            let new_pos = spawn_area.sample(|| rng.gen());
             let new_radius = rand_init_radius();
             data.get_mut::<Vec<Vec2>>("positions")?[i] = new_pos;
             data.get_mut::<Vec<f32>>("radii")?[i] = new_radius;
@@ -77,14 +80,6 @@ fn compute(sled: &Sled<Rgb>, data: &mut Data, time: &Time) -> SledResult {
     Ok(())
 }
 
-fn rand_point_in_range(range: &Range<Vec2>) -> Vec2 {
-    let mut rng = rand::thread_rng();
-    Vec2::new(
-        rng.gen_range(range.start.x * 1.25..range.end.x * 1.25),
-        rng.gen_range(range.start.y * 1.25..range.end.y * 1.25),
-    )
-}
-
 fn rand_init_radius() -> f32 {
     let mut rng = rand::thread_rng();
     // using a negative radius, we can scheudle a delay before the ripple actually appears
@@ -0,0 +1,87 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use palette::rgb::Rgb;
+use spatial_led::{Filter, Sled};
+
+/// The set-based approach Filter used before switching to a bitset, kept as a baseline.
+fn btree_and(a: &BTreeSet<u16>, b: &BTreeSet<u16>) -> BTreeSet<u16> {
+    let mut filtered = a.clone();
+    for led in a {
+        if !b.contains(led) {
+            filtered.remove(led);
+        }
+    }
+    filtered
+}
+
+fn btree_or(a: &BTreeSet<u16>, b: &BTreeSet<u16>) -> BTreeSet<u16> {
+    let mut extended = a.clone();
+    for led in b {
+        extended.insert(*led);
+    }
+    extended
+}
+
+fn set_operations(c: &mut Criterion) {
+    let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    let left = sled.filter_by_pos(|pos| pos.x < sled.center_point().x);
+    let top = sled.filter_by_pos(|pos| pos.y > sled.center_point().y);
+    let left_set: BTreeSet<u16> = left.iter().collect();
+    let top_set: BTreeSet<u16> = top.iter().collect();
+
+    // a layout far larger than the bench config, selecting half the strip
+    let num_leds = 40_000u16;
+    let evens: Filter = (0..num_leds).step_by(2).collect();
+    let first_half: Filter = (0..num_leds / 2).collect();
+    let evens_set: BTreeSet<u16> = evens.iter().collect();
+    let first_half_set: BTreeSet<u16> = first_half.iter().collect();
+
+    let mut group = c.benchmark_group("filter_and");
+    group.bench_function("bitset/config", |b| b.iter(|| black_box(left.and(&top))));
+    group.bench_function("btree/config", |b| {
+        b.iter(|| black_box(btree_and(&left_set, &top_set)))
+    });
+    group.bench_function("bitset/large", |b| {
+        b.iter(|| black_box(evens.and(&first_half)))
+    });
+    group.bench_function("btree/large", |b| {
+        b.iter(|| black_box(btree_and(&evens_set, &first_half_set)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("filter_or");
+    group.bench_function("bitset/config", |b| b.iter(|| black_box(left.or(&top))));
+    group.bench_function("btree/config", |b| {
+        b.iter(|| black_box(btree_or(&left_set, &top_set)))
+    });
+    group.bench_function("bitset/large", |b| {
+        b.iter(|| black_box(evens.or(&first_half)))
+    });
+    group.bench_function("btree/large", |b| {
+        b.iter(|| black_box(btree_or(&evens_set, &first_half_set)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("filter_iter");
+    group.bench_function("bitset/large", |b| {
+        b.iter(|| black_box(evens.iter().map(|i| i as u32).sum::<u32>()))
+    });
+    group.bench_function("btree/large", |b| {
+        b.iter(|| black_box(evens_set.iter().map(|i| *i as u32).sum::<u32>()))
+    });
+    group.finish();
+}
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .significance_level(0.05)
+        .sample_size(50)
+        .warm_up_time(Duration::from_secs_f32(1.0))
+        .measurement_time(Duration::from_secs_f32(3.0));
+    targets = set_operations
+}
+criterion_main!(benches);
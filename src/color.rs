@@ -2,3 +2,238 @@ use std::fmt::Debug;
 pub trait ColorType: Debug + Default + Copy {}
 
 impl<T: Debug + Default + Copy> ColorType for T {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+/// Describes how a new (source) color is combined with the color already on an [LED](crate::Led) (the destination). See [Blend].
+pub enum BlendMode {
+    /// Sums the two colors. Good for glows and light that accumulates.
+    Add,
+    /// Multiplies the two colors channel by channel. Only ever darkens.
+    Multiply,
+    /// The inverse of multiplying the inverses. Only ever brightens, but never past 1.
+    Screen,
+    /// Places the source on top of the destination, weighted by the source's alpha. Colors without an alpha channel simply replace the destination.
+    #[default]
+    AlphaOver,
+    /// Takes the greater of each channel.
+    Max,
+    /// Takes the lesser of each channel.
+    Min,
+    /// Keeps whichever of the two colors has the higher [luminance](Blend::luminance), as a whole.
+    /// Unlike [BlendMode::Max], channels are never mixed between the two colors.
+    Lighten,
+}
+
+/// Lets a color type be mixed with others using a [BlendMode].
+///
+/// Implemented for `f32`, tuples and arrays of `f32`, and (behind the `palette` feature flag) palette's `Rgb`, `Luma` and `Alpha` types.
+/// To support your own color type, implement [Blend::zip_channels()] and [Blend::luminance()]; colors with transparency should also implement [Blend::alpha()] and [Blend::with_alpha()].
+///
+/// Blending uses straight (non-premultiplied) alpha: the result of the blend mode is mixed over the destination by the source's alpha.
+///
+/// ```rust
+/// use spatial_led::color::{Blend, BlendMode};
+/// let base = (0.25, 0.5, 0.75);
+/// let light = (0.5, 0.5, 0.5);
+/// assert_eq!(base.blend(light, BlendMode::Add), (0.75, 1.0, 1.25));
+/// assert_eq!(base.blend(light, BlendMode::Max), (0.5, 0.5, 0.75));
+/// assert_eq!(base.blend(light, BlendMode::AlphaOver), light);
+/// ```
+pub trait Blend: Copy {
+    /// Combines two colors channel by channel. Alpha channels, if any, should be left untouched.
+    fn zip_channels(self, other: Self, op: impl Fn(f32, f32) -> f32) -> Self;
+
+    /// Returns the relative luminance of the color, used by [BlendMode::Lighten].
+    fn luminance(self) -> f32;
+
+    /// Returns the opacity of the color. Defaults to fully opaque.
+    fn alpha(self) -> f32 {
+        1.0
+    }
+
+    /// Returns a copy of the color with its opacity set to `alpha`. Colors without an alpha channel return themselves unchanged.
+    fn with_alpha(self, alpha: f32) -> Self {
+        let _ = alpha;
+        self
+    }
+
+    /// Linearly interpolates from this color toward `other`, alpha included. A `t` of 0 returns this color, 1 returns `other`.
+    fn mix(self, other: Self, t: f32) -> Self {
+        let alpha = self.alpha() + (other.alpha() - self.alpha()) * t;
+        self.zip_channels(other, |a, b| a + (b - a) * t)
+            .with_alpha(alpha)
+    }
+
+    /// Blends `src` on top of this color according to `mode`.
+    fn blend(self, src: Self, mode: BlendMode) -> Self {
+        self.blend_with_opacity(src, mode, 1.0)
+    }
+
+    /// Blends `src` on top of this color according to `mode`, then mixes the result over this color by `opacity`.
+    ///
+    /// An opacity of 0 leaves this color untouched. The source's own alpha is multiplied in as well.
+    fn blend_with_opacity(self, src: Self, mode: BlendMode, opacity: f32) -> Self {
+        let blended = match mode {
+            BlendMode::Add => self.zip_channels(src, |d, s| d + s),
+            BlendMode::Multiply => self.zip_channels(src, |d, s| d * s),
+            BlendMode::Screen => self.zip_channels(src, |d, s| d + s - d * s),
+            BlendMode::AlphaOver => self.zip_channels(src, |_, s| s),
+            BlendMode::Max => self.zip_channels(src, f32::max),
+            BlendMode::Min => self.zip_channels(src, f32::min),
+            BlendMode::Lighten => {
+                if src.luminance() > self.luminance() {
+                    self.zip_channels(src, |_, s| s)
+                } else {
+                    self
+                }
+            }
+        };
+
+        let amount = (src.alpha() * opacity).clamp(0.0, 1.0);
+        if amount >= 1.0 {
+            return blended.with_alpha(1.0);
+        }
+
+        self.zip_channels(blended, |d, b| d + (b - d) * amount)
+            .with_alpha(amount + self.alpha() * (1.0 - amount))
+    }
+}
+
+// Rec. 709 luma coefficients; three-channel colors are assumed to be RGB.
+fn rgb_luminance(r: f32, g: f32, b: f32) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+impl Blend for f32 {
+    fn zip_channels(self, other: Self, op: impl Fn(f32, f32) -> f32) -> Self {
+        op(self, other)
+    }
+
+    fn luminance(self) -> f32 {
+        self
+    }
+}
+
+impl Blend for (f32, f32) {
+    fn zip_channels(self, other: Self, op: impl Fn(f32, f32) -> f32) -> Self {
+        (op(self.0, other.0), op(self.1, other.1))
+    }
+
+    fn luminance(self) -> f32 {
+        (self.0 + self.1) * 0.5
+    }
+}
+
+impl Blend for (f32, f32, f32) {
+    fn zip_channels(self, other: Self, op: impl Fn(f32, f32) -> f32) -> Self {
+        (
+            op(self.0, other.0),
+            op(self.1, other.1),
+            op(self.2, other.2),
+        )
+    }
+
+    fn luminance(self) -> f32 {
+        rgb_luminance(self.0, self.1, self.2)
+    }
+}
+
+impl Blend for (f32, f32, f32, f32) {
+    fn zip_channels(self, other: Self, op: impl Fn(f32, f32) -> f32) -> Self {
+        (
+            op(self.0, other.0),
+            op(self.1, other.1),
+            op(self.2, other.2),
+            op(self.3, other.3),
+        )
+    }
+
+    fn luminance(self) -> f32 {
+        (self.0 + self.1 + self.2 + self.3) * 0.25
+    }
+}
+
+impl<const N: usize> Blend for [f32; N] {
+    fn zip_channels(self, other: Self, op: impl Fn(f32, f32) -> f32) -> Self {
+        core::array::from_fn(|i| op(self[i], other[i]))
+    }
+
+    fn luminance(self) -> f32 {
+        if N == 3 {
+            rgb_luminance(self[0], self[1], self[2])
+        } else {
+            self.iter().sum::<f32>() / N.max(1) as f32
+        }
+    }
+}
+
+/// Re-exported so that Sled projects can use palette without depending on it directly, and always get a version Sled's traits are implemented for.
+#[cfg(feature = "palette")]
+pub use palette;
+
+/// [palette's Rgb struct](https://docs.rs/palette/latest/palette/rgb/struct.Rgb.html), brought forward so that `Sled::<Rgb>` works with a single import.
+///
+/// ```rust
+/// use spatial_led::{color::Rgb, output::OutputEncoder, Sled};
+/// let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+/// sled.set_all(Rgb::new(1.0, 0.5, 0.0));
+///
+/// let mut encoder = OutputEncoder::default();
+/// assert_eq!(&encoder.encode(&sled)[0..3], &[255, 128, 0]);
+/// ```
+#[cfg(feature = "palette")]
+pub use palette::rgb::Rgb;
+
+#[cfg(feature = "palette")]
+mod palette_impls {
+    use super::{rgb_luminance, Blend};
+    use palette::{luma::Luma, rgb::Rgb, Alpha};
+
+    impl<S> Blend for Rgb<S, f32> {
+        fn zip_channels(self, other: Self, op: impl Fn(f32, f32) -> f32) -> Self {
+            Rgb::new(
+                op(self.red, other.red),
+                op(self.green, other.green),
+                op(self.blue, other.blue),
+            )
+        }
+
+        fn luminance(self) -> f32 {
+            rgb_luminance(self.red, self.green, self.blue)
+        }
+    }
+
+    impl<S> Blend for Luma<S, f32> {
+        fn zip_channels(self, other: Self, op: impl Fn(f32, f32) -> f32) -> Self {
+            Luma::new(op(self.luma, other.luma))
+        }
+
+        fn luminance(self) -> f32 {
+            self.luma
+        }
+    }
+
+    impl<C: Blend> Blend for Alpha<C, f32> {
+        fn zip_channels(self, other: Self, op: impl Fn(f32, f32) -> f32) -> Self {
+            Alpha {
+                color: self.color.zip_channels(other.color, op),
+                alpha: self.alpha,
+            }
+        }
+
+        fn luminance(self) -> f32 {
+            self.color.luminance()
+        }
+
+        fn alpha(self) -> f32 {
+            self.alpha
+        }
+
+        fn with_alpha(self, alpha: f32) -> Self {
+            Alpha {
+                color: self.color,
+                alpha,
+            }
+        }
+    }
+}
@@ -5,6 +5,7 @@ use alloc::vec::Vec;
 
 use crate::error::SledError;
 use crate::Vec2;
+use core::ops::Range;
 use core::str::Lines;
 use smallvec::SmallVec;
 
@@ -15,6 +16,8 @@ pub(crate) struct Config {
     pub center_point: Vec2,
     pub density: f32,
     pub line_segments: Vec<LineSegment>,
+    /// Ranges of `line_segments` indices, one per chain of connected segments.
+    pub chains: Vec<Range<usize>>,
 }
 
 fn extract_center_and_density_from_lines(lines: &mut Lines) -> (Option<Vec2>, Option<f32>) {
@@ -81,10 +84,12 @@ fn lines_to_string(lines: &mut Lines) -> String {
     composite
 }
 
-fn extract_segments_from_string(s: &str) -> Vec<LineSegment> {
+fn extract_segments_from_string(s: &str) -> (Vec<LineSegment>, Vec<Range<usize>>) {
     let connected: Vec<&str> = s.split("|").collect();
     let mut segments: Vec<LineSegment> = vec![];
+    let mut chains: Vec<Range<usize>> = vec![];
     for sequence in connected {
+        let chain_start = segments.len();
         let vertex_strings: Vec<&str> = sequence.split("-->").map(|s| s.trim()).collect();
         let mut last_vertex: Option<Vec2> = None;
         for vertex_string in vertex_strings {
@@ -97,9 +102,13 @@ fn extract_segments_from_string(s: &str) -> Vec<LineSegment> {
             }
             last_vertex = Some(vertex);
         }
+
+        if segments.len() > chain_start {
+            chains.push(chain_start..segments.len());
+        }
     }
 
-    segments
+    (segments, chains)
 }
 
 impl Config {
@@ -120,12 +129,13 @@ impl Config {
         }
 
         let back_to_str = lines_to_string(&mut lines);
-        let line_segments = extract_segments_from_string(&back_to_str);
+        let (line_segments, chains) = extract_segments_from_string(&back_to_str);
 
         Ok(Config {
             density: density.unwrap(),
             center_point: center.unwrap(),
             line_segments,
+            chains,
         })
     }
 
@@ -175,6 +185,28 @@ impl LineSegment {
         }
     }
 
+    /// Like [LineSegment::intersects_line], but against an unbounded ray.
+    /// Returns the alpha along this segment and the distance along the ray (in units of `dir`'s length).
+    pub fn intersects_ray(&self, origin: Vec2, dir: Vec2) -> Option<(f32, f32)> {
+        let s1 = self.end - self.start;
+        let denom = dir.perp_dot(s1);
+
+        // check if parallel
+        if denom.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let start_dif = self.start - origin;
+        let ray_dist = start_dif.perp_dot(s1) / denom;
+        let t = start_dif.perp_dot(dir) / denom;
+
+        if (0.0..=1.0).contains(&t) && ray_dist >= 0.0 {
+            Some((t, ray_dist))
+        } else {
+            None
+        }
+    }
+
     pub fn intersects_circle(&self, circle_center: Vec2, circle_radius: f32) -> SmallVec<[f32; 2]> {
         let v1 = self.end - self.start;
         let v2 = self.start - circle_center;
@@ -19,6 +19,10 @@ pub struct Time {
 
 type SledResult = Result<(), SledError>;
 
+type StartupCommands<COLOR> = Box<dyn Fn(&mut Sled<COLOR>, &mut Data) -> SledResult>;
+type ComputeCommands<COLOR> = Box<dyn Fn(&Sled<COLOR>, &mut Data, &Time) -> SledResult>;
+type DrawCommands<COLOR> = Box<dyn Fn(&mut Sled<COLOR>, &Data, &Time) -> SledResult>;
+
 /// Drivers are useful for encapsulating everything you need to drive a complicated lighting effect all in one place.
 pub struct CustomDriver<INSTANT, COLOR>
 where
@@ -26,9 +30,9 @@ where
     COLOR: ColorType,
 {
     sled: Option<Sled<COLOR>>,
-    startup_commands: Box<dyn Fn(&mut Sled<COLOR>, &mut Data) -> SledResult>,
-    compute_commands: Box<dyn Fn(&Sled<COLOR>, &mut Data, &Time) -> SledResult>,
-    draw_commands: Box<dyn Fn(&mut Sled<COLOR>, &Data, &Time) -> SledResult>,
+    startup_commands: StartupCommands<COLOR>,
+    compute_commands: ComputeCommands<COLOR>,
+    draw_commands: DrawCommands<COLOR>,
     startup: INSTANT,
     last_update: INSTANT,
 
@@ -167,6 +171,7 @@ where
             };
 
             self.last_update = INSTANT::now();
+            sled.record_frame();
             (self.compute_commands)(sled, &mut self.data, &time).unwrap();
             (self.draw_commands)(sled, &self.data, &time).unwrap();
         }
@@ -183,7 +188,7 @@ where
     }
 
     /// See [Sled::leds()].
-    pub fn leds(&self) -> impl Iterator<Item = &Led<COLOR>> {
+    pub fn leds(&self) -> impl Iterator<Item = Led<'_, COLOR>> {
         if let Some(sled) = &self.sled {
             sled.leds()
         } else {
@@ -0,0 +1,371 @@
+//! Maps scalars like distance, angle or path position to colors.
+//!
+//! A [Gradient] blends between colors placed at arbitrary positions, while a [Palette] is an evenly spaced, cyclic list of colors that can also be indexed directly.
+//! Both implement [ColorMap], which the gradient variants of Sled's map methods (like [Sled::map_by_angle_gradient()]) accept.
+//!
+//! They work with any color type that implements [Blend], since that's what lets two colors be [mixed](Blend::mix).
+//!
+//! ```rust
+//! # use spatial_led::Sled;
+//! use spatial_led::gradient::Palette;
+//! # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+//! let rainbow = Palette::new([
+//!     (1.0, 0.0, 0.0),
+//!     (1.0, 1.0, 0.0),
+//!     (0.0, 1.0, 0.0),
+//!     (0.0, 1.0, 1.0),
+//!     (0.0, 0.0, 1.0),
+//!     (1.0, 0.0, 1.0),
+//! ]);
+//! sled.map_by_angle_gradient(&rainbow);
+//! ```
+
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::float::Float as _;
+
+use crate::{
+    color::{Blend, ColorType},
+    led::Led,
+    Sled, Vec2,
+};
+
+/// Anything that maps a scalar to a color, like a [Gradient] or a [Palette].
+pub trait ColorMap<Color> {
+    /// Returns the color at `t`.
+    fn sample(&self, t: f32) -> Color;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+/// How colors are blended between two neighboring stops.
+pub enum Interpolation {
+    /// Blends at a constant rate from one stop to the next.
+    #[default]
+    Linear,
+    /// Eases in and out of each stop with a smoothstep curve, hiding the seams between stops.
+    Smooth,
+    /// Holds each stop's color until the next one, with no blending.
+    Step,
+}
+
+impl Interpolation {
+    fn shape(self, t: f32) -> f32 {
+        match self {
+            Interpolation::Linear => t,
+            Interpolation::Smooth => t * t * (3.0 - 2.0 * t),
+            Interpolation::Step => 0.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+/// What a [Gradient] does with positions outside of its stops.
+pub enum Spread {
+    /// Positions before the first stop take its color, and positions after the last stop take that one's.
+    #[default]
+    Clamp,
+    /// Positions wrap around every 1.0 units, and the last stop blends back into the first. Stops are expected to lie between 0 and 1.
+    Cycle,
+}
+
+/// Wraps `t` into the range [0, 1).
+fn wrap_unit(t: f32) -> f32 {
+    let wrapped = t - t.floor();
+    // floating point error can land exactly on 1.0 for tiny negative inputs, and non-finite inputs wrap to NaN
+    if wrapped >= 1.0 || wrapped.is_nan() {
+        0.0
+    } else {
+        wrapped
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A series of colors placed at positions along a line, blended in between.
+///
+/// ```rust
+/// use spatial_led::gradient::{ColorMap, Gradient, Interpolation};
+/// let mut heat = Gradient::new([
+///     (0.0, (0.0, 0.0, 0.0)),
+///     (0.5, (1.0, 0.0, 0.0)),
+///     (1.0, (1.0, 1.0, 0.0)),
+/// ]);
+/// assert_eq!(heat.sample(0.25), (0.5, 0.0, 0.0));
+/// assert_eq!(heat.sample(2.0), (1.0, 1.0, 0.0));
+/// // non-finite positions sample the first stop
+/// assert_eq!(heat.sample(f32::NAN), (0.0, 0.0, 0.0));
+///
+/// heat.set_interpolation(Interpolation::Step);
+/// assert_eq!(heat.sample(0.75), (1.0, 0.0, 0.0));
+/// ```
+pub struct Gradient<Color> {
+    stops: Vec<(f32, Color)>,
+    interpolation: Interpolation,
+    spread: Spread,
+}
+
+impl<Color: ColorType + Blend> Gradient<Color> {
+    /// Creates a Gradient from `(position, color)` stops, with linear interpolation and clamped ends.
+    ///
+    /// Stops don't need to be given in order. A Gradient without any stops samples as `Color::default()`.
+    pub fn new(stops: impl IntoIterator<Item = (f32, Color)>) -> Self {
+        let mut stops: Vec<(f32, Color)> = stops.into_iter().collect();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Gradient {
+            stops,
+            interpolation: Interpolation::default(),
+            spread: Spread::default(),
+        }
+    }
+
+    /// Creates a Gradient that places `colors` evenly from 0 to 1.
+    pub fn evenly_spaced(colors: impl IntoIterator<Item = Color>) -> Self {
+        let colors: Vec<Color> = colors.into_iter().collect();
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        Gradient::new(
+            colors
+                .into_iter()
+                .enumerate()
+                .map(|(i, color)| (i as f32 / last, color)),
+        )
+    }
+
+    /// Returns the Gradient's `(position, color)` stops, ordered by position.
+    pub fn stops(&self) -> &[(f32, Color)] {
+        &self.stops
+    }
+
+    /// Adds a stop at `position`, keeping the stops ordered.
+    pub fn add_stop(&mut self, position: f32, color: Color) {
+        let index = self.stops.partition_point(|stop| stop.0 <= position);
+        self.stops.insert(index, (position, color));
+    }
+
+    /// Returns how colors are blended between stops.
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// Sets how colors are blended between stops.
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+
+    /// Returns what the Gradient does with positions outside of its stops.
+    pub fn spread(&self) -> Spread {
+        self.spread
+    }
+
+    /// Sets what the Gradient does with positions outside of its stops.
+    ///
+    /// ```rust
+    /// use spatial_led::gradient::{ColorMap, Gradient, Spread};
+    /// let mut pulse = Gradient::new([(0.0, 0.0), (0.5, 1.0)]);
+    /// pulse.set_spread(Spread::Cycle);
+    /// // halfway from the last stop back around to the first
+    /// assert_eq!(pulse.sample(0.75), 0.5);
+    /// assert_eq!(pulse.sample(1.25), 0.5);
+    /// ```
+    pub fn set_spread(&mut self, spread: Spread) {
+        self.spread = spread;
+    }
+
+    fn mix(&self, from: Color, to: Color, t: f32) -> Color {
+        from.mix(to, self.interpolation.shape(t.clamp(0.0, 1.0)))
+    }
+}
+
+impl<Color: ColorType + Blend> ColorMap<Color> for Gradient<Color> {
+    /// O(log(STOPS))
+    fn sample(&self, t: f32) -> Color {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return Color::default(),
+        };
+
+        if !t.is_finite() {
+            return first.1;
+        }
+
+        let t = match self.spread {
+            Spread::Clamp => t,
+            Spread::Cycle => {
+                let mut wrapped = wrap_unit(t);
+                if wrapped < first.0 {
+                    wrapped += 1.0;
+                }
+
+                if wrapped >= last.0 {
+                    let span = first.0 + 1.0 - last.0;
+                    if span <= 0.0 {
+                        return last.1;
+                    }
+                    return self.mix(last.1, first.1, (wrapped - last.0) / span);
+                }
+                wrapped
+            }
+        };
+
+        if t <= first.0 {
+            return first.1;
+        } else if t >= last.0 {
+            return last.1;
+        }
+
+        let next = self.stops.partition_point(|stop| stop.0 <= t);
+        let (from, to) = (self.stops[next - 1], self.stops[next]);
+        let span = to.0 - from.0;
+        if span <= 0.0 {
+            return to.1;
+        }
+
+        self.mix(from.1, to.1, (t - from.0) / span)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A cyclic list of colors. Sampling spreads them evenly from 0 to 1, blending the last color back into the first.
+///
+/// ```rust
+/// use spatial_led::gradient::{ColorMap, Palette};
+/// let palette = Palette::new([0.0, 1.0]);
+/// assert_eq!(palette.sample(0.25), 0.5);
+/// assert_eq!(palette.sample(0.75), 0.5);
+/// assert_eq!(palette.get(3), 1.0);
+/// ```
+pub struct Palette<Color> {
+    colors: Vec<Color>,
+    interpolation: Interpolation,
+}
+
+impl<Color: ColorType + Blend> Palette<Color> {
+    /// Creates a Palette from a list of colors, with linear interpolation.
+    ///
+    /// A Palette without any colors samples as `Color::default()`.
+    pub fn new(colors: impl IntoIterator<Item = Color>) -> Self {
+        Palette {
+            colors: colors.into_iter().collect(),
+            interpolation: Interpolation::default(),
+        }
+    }
+
+    /// Returns the color at `index`, wrapping around if it's past the end of the Palette.
+    pub fn get(&self, index: usize) -> Color {
+        if self.colors.is_empty() {
+            return Color::default();
+        }
+        self.colors[index % self.colors.len()]
+    }
+
+    /// Returns the Palette's colors.
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    /// Returns the number of colors in the Palette.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Returns true if the Palette holds no colors.
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Returns how colors are blended between neighbors.
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// Sets how colors are blended between neighbors.
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+}
+
+impl<Color: ColorType + Blend> ColorMap<Color> for Palette<Color> {
+    /// O(1)
+    fn sample(&self, t: f32) -> Color {
+        if self.colors.is_empty() {
+            return Color::default();
+        }
+
+        let scaled = wrap_unit(t) * self.colors.len() as f32;
+        let index = scaled as usize;
+        let amount = self.interpolation.shape(scaled - index as f32);
+        self.get(index).mix(self.get(index + 1), amount)
+    }
+}
+
+/// # Gradient-based write methods
+///
+/// Counterparts to the usual map methods that look colors up in a [ColorMap], such as a [Gradient] or [Palette].
+impl<Color: ColorType + Blend> Sled<Color> {
+    /// Sets each [LED](Led) to the color `gradient` samples at the position `led_to_t` returns.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    /// # use spatial_led::Sled;
+    /// use spatial_led::gradient::Gradient;
+    /// # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// let fade = Gradient::evenly_spaced([(0.0, 0.0, 1.0), (1.0, 0.0, 0.0)]);
+    /// // blue at the bottom of the room, red at the top
+    /// sled.map_gradient(&fade, |led| led.position().y / 2.0);
+    /// ```
+    pub fn map_gradient(
+        &mut self,
+        gradient: &impl ColorMap<Color>,
+        led_to_t: impl Fn(&Led<Color>) -> f32,
+    ) {
+        self.map(|led| gradient.sample(led_to_t(led)));
+    }
+
+    /// Colors each [LED](Led) by its distance from the center point, sampling `gradient` from 0 at the center to 1 at `max_dist`.
+    /// If `max_dist` isn't positive, every LED samples 0.
+    ///
+    /// O(LEDS)
+    pub fn map_by_dist_gradient(&mut self, max_dist: f32, gradient: &impl ColorMap<Color>) {
+        let inv_max_dist = inverse_or_zero(max_dist);
+        self.map_gradient(gradient, |led| led.distance() * inv_max_dist);
+    }
+
+    /// Colors each [LED](Led) by its distance from `pos`, sampling `gradient` from 0 at `pos` to 1 at `max_dist`.
+    /// If `max_dist` isn't positive, every LED samples 0.
+    ///
+    /// O(LEDS)
+    pub fn map_by_dist_from_gradient(
+        &mut self,
+        pos: Vec2,
+        max_dist: f32,
+        gradient: &impl ColorMap<Color>,
+    ) {
+        let inv_max_dist = inverse_or_zero(max_dist);
+        self.map_gradient(gradient, |led| led.position().distance(pos) * inv_max_dist);
+    }
+
+    /// Colors each [LED](Led) by its angle around the center point, sampling `gradient` from 0 to 1 over a full counter-clockwise turn. See [Led::normalized_angle()].
+    ///
+    /// O(LEDS)
+    pub fn map_by_angle_gradient(&mut self, gradient: &impl ColorMap<Color>) {
+        self.map_gradient(gradient, |led| led.normalized_angle());
+    }
+
+    /// Colors each [LED](Led) by its [path position](Led::path_pos), sampling `gradient` from 0 at the start of the path to 1 at the end.
+    /// If the path has no length, every LED samples 0.
+    ///
+    /// O(LEDS)
+    pub fn map_by_path_pos_gradient(&mut self, gradient: &impl ColorMap<Color>) {
+        let inv_path_length = inverse_or_zero(self.path_length());
+        self.map_gradient(gradient, |led| led.path_pos() * inv_path_length);
+    }
+}
+
+/// Returns `1 / x`, or 0 if `x` isn't positive, so that degenerate ranges sample the start of a gradient rather than dividing by zero.
+fn inverse_or_zero(x: f32) -> f32 {
+    if x > 0.0 {
+        x.recip()
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,266 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+use crate::{
+    color::{Blend, BlendMode, ColorType},
+    Mask, Sled, SledError,
+};
+
+#[derive(Clone, Debug)]
+/// A named color buffer that shares the geometry of the [Sled] it was created from.
+///
+/// Layers dereference to a [Sled], so every drawing method (`set_filter`, `map_by_pos`, `modulate_within_dist`, ...) works on a Layer exactly as it would on the Sled itself.
+/// How a Layer combines with the ones beneath it is controlled by its [opacity](Layer::set_opacity), [blend mode](Layer::set_blend_mode) and optional [mask](Layer::set_mask).
+pub struct Layer<Color: ColorType> {
+    name: String,
+    sled: Sled<Color>,
+    opacity: f32,
+    blend_mode: BlendMode,
+    mask: Option<Mask>,
+}
+
+impl<Color: ColorType> Layer<Color> {
+    /// Returns the name the Layer was created with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the opacity of the Layer, between 0 and 1.
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Sets the opacity of the Layer. Values are clamped between 0 and 1; an opacity of 0 hides the Layer entirely.
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    /// Returns the [BlendMode] used to combine this Layer with the ones beneath it.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Sets the [BlendMode] used to combine this Layer with the ones beneath it.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Returns the [Mask] limiting where this Layer is visible, if there is one.
+    pub fn mask(&self) -> Option<&Mask> {
+        self.mask.as_ref()
+    }
+
+    /// Sets or clears the [Mask] limiting where this Layer is visible. Each LED's weight in the mask is multiplied with the Layer's opacity.
+    pub fn set_mask(&mut self, mask: Option<Mask>) {
+        self.mask = mask;
+    }
+
+    /// Returns the Layer's underlying [Sled].
+    pub fn sled(&self) -> &Sled<Color> {
+        &self.sled
+    }
+
+    /// Returns the Layer's underlying [Sled] mutably.
+    pub fn sled_mut(&mut self) -> &mut Sled<Color> {
+        &mut self.sled
+    }
+}
+
+impl<Color: ColorType> Deref for Layer<Color> {
+    type Target = Sled<Color>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.sled
+    }
+}
+
+impl<Color: ColorType> DerefMut for Layer<Color> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.sled
+    }
+}
+
+#[derive(Clone, Debug)]
+/// An ordered stack of [Layers](Layer) that can be flattened onto a [Sled].
+///
+/// Layers are composited from the bottom of the stack (the first one added) to the top, each blended over the result of the ones beneath it.
+///
+/// Every Layer starts out filled with `Color::default()`. For color types with an alpha channel that means fully transparent; for opaque types you'll want blend modes like [BlendMode::Add] or [BlendMode::Screen], where the default color (usually black) has no effect.
+///
+/// ```rust
+/// # use spatial_led::{Sled, Vec2, layers::LayerStack, color::BlendMode};
+/// # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+/// let mut layers = LayerStack::new();
+///
+/// let background = layers.add("background", &sled);
+/// background.set_all((0.0, 0.0, 0.2));
+///
+/// let accents = layers.add("accents", &sled);
+/// accents.set_blend_mode(BlendMode::Add);
+/// accents.set_opacity(0.5);
+/// accents.set_within_dist_from(1.0, Vec2::new(1.0, 1.0), (1.0, 0.5, 0.0));
+///
+/// // later, from anywhere that has the stack:
+/// layers.get_mut("accents").unwrap().set_opacity(0.8);
+///
+/// layers.composite(&mut sled).unwrap();
+/// ```
+pub struct LayerStack<Color: ColorType> {
+    layers: Vec<Layer<Color>>,
+}
+
+impl<Color: ColorType> Default for LayerStack<Color> {
+    fn default() -> Self {
+        LayerStack { layers: Vec::new() }
+    }
+}
+
+impl<Color: ColorType> LayerStack<Color> {
+    /// Creates an empty LayerStack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new Layer named `name` to the top of the stack, sharing the geometry of `sled`, and returns it for drawing.
+    ///
+    /// If a Layer with that name already exists, it is replaced with a fresh one and moved to the top.
+    ///
+    /// New Layers start out with every LED set to `Color::default()` and no [frame history](Sled::set_history_len), whatever state `sled` is in.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, layers::LayerStack};
+    /// # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// sled.set_history_len(4);
+    /// sled.set_all((1.0, 1.0, 1.0));
+    ///
+    /// let mut layers = LayerStack::new();
+    /// let layer = layers.add("sparkles", &sled);
+    /// assert_eq!(layer.get(0).unwrap().color, (0.0, 0.0, 0.0));
+    /// assert_eq!(layer.history_len(), 0);
+    /// ```
+    pub fn add(&mut self, name: &str, sled: &Sled<Color>) -> &mut Layer<Color> {
+        self.remove(name);
+
+        let sled = Sled::from_layout(Arc::clone(sled.layout()));
+        self.layers.push(Layer {
+            name: String::from(name),
+            sled,
+            opacity: 1.0,
+            blend_mode: BlendMode::default(),
+            mask: None,
+        });
+
+        self.layers.last_mut().unwrap()
+    }
+
+    /// Removes the Layer named `name` from the stack and returns it, if it exists.
+    pub fn remove(&mut self, name: &str) -> Option<Layer<Color>> {
+        let position = self.position(name)?;
+        Some(self.layers.remove(position))
+    }
+
+    /// Returns the Layer named `name`, if it exists.
+    pub fn get(&self, name: &str) -> Option<&Layer<Color>> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    /// Returns the Layer named `name` mutably, if it exists.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Layer<Color>> {
+        self.layers.iter_mut().find(|layer| layer.name == name)
+    }
+
+    /// Returns the position of the Layer named `name` in the stack, where 0 is the bottom.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|layer| layer.name == name)
+    }
+
+    /// Moves the Layer named `name` to `position` in the stack, shifting the others to make room.
+    /// Returns an [error](SledError) if no such Layer exists or the position is out of bounds.
+    pub fn move_to(&mut self, name: &str, position: usize) -> Result<(), SledError> {
+        let Some(current) = self.position(name) else {
+            return SledError::new(format!("No layer named {} exists.", name)).as_err();
+        };
+
+        if position >= self.layers.len() {
+            return SledError::new(format!(
+                "Position {} is out of bounds for a stack of {} layers.",
+                position,
+                self.layers.len()
+            ))
+            .as_err();
+        }
+
+        let layer = self.layers.remove(current);
+        self.layers.insert(position, layer);
+        Ok(())
+    }
+
+    /// Returns an Iterator over each Layer, from the bottom of the stack to the top.
+    pub fn iter(&self) -> impl Iterator<Item = &Layer<Color>> {
+        self.layers.iter()
+    }
+
+    /// Returns a mutable Iterator over each Layer, from the bottom of the stack to the top.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Layer<Color>> {
+        self.layers.iter_mut()
+    }
+
+    /// Returns the number of Layers in the stack.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns true if the stack holds no Layers.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+}
+
+impl<Color: ColorType + Blend> LayerStack<Color> {
+    /// Flattens every Layer onto `sled`, from the bottom of the stack to the top, blending each over the sled's current colors.
+    ///
+    /// To composite onto a blank canvas, clear the sled first with [Sled::set_all()].
+    ///
+    /// Returns an [error](SledError) if any Layer holds a different number of LEDs than `sled`.
+    ///
+    /// O(LEDS * LAYERS)
+    pub fn composite(&self, sled: &mut Sled<Color>) -> Result<(), SledError> {
+        if let Some(layer) = self
+            .layers
+            .iter()
+            .find(|layer| layer.num_leds() != sled.num_leds())
+        {
+            return SledError::new(format!(
+                "Layer {} holds {} LEDs, but the target holds {}.",
+                layer.name,
+                layer.num_leds(),
+                sled.num_leds()
+            ))
+            .as_err();
+        }
+
+        for layer in &self.layers {
+            if layer.opacity <= 0.0 {
+                continue;
+            }
+
+            let mut colors = layer.colors();
+            sled.for_each(|led| {
+                let src = *colors.next().unwrap();
+                let weight = match &layer.mask {
+                    Some(mask) => layer.opacity * mask.get(led.index() as usize),
+                    None => layer.opacity,
+                };
+
+                if weight > 0.0 {
+                    led.color = led.color.blend_with_opacity(src, layer.blend_mode, weight);
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
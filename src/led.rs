@@ -1,3 +1,5 @@
+use core::f32::consts::TAU;
+
 use crate::Vec2;
 
 #[cfg(not(feature = "std"))]
@@ -5,124 +7,318 @@ use num_traits::float::Float as _;
 
 use crate::color::ColorType;
 
-#[derive(Copy, Clone)]
-
-/// An LED in our Sled configuration, representing both the color of the LED as well as it's spatial information.
-pub struct Led<Color: ColorType> {
-    pub color: Color,
+/// The spatial information of a single LED, shared between every color buffer that uses the same [Layout](crate::Layout).
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct LedGeometry {
     position: Vec2,
     angle: f32,
     distance: f32,
     index: u16,
     segment: u8,
+    chain_pos: f32,
+    path_pos: f32,
+    tangent: Vec2,
+    normal: Vec2,
+    uv: Vec2,
+    square_uv: Vec2,
+    normalized_distance: f32,
 }
 
-/// *All properties listed below are pre-calculated on construction;
-/// there is no substantial overhead for calling these methods.*
-impl<Color: ColorType> Led<Color> {
-    /// Constructs an LED struct.
+impl LedGeometry {
     /// Fields like `position`, `angle`, and `distance` are derived from `center_point`.
+    /// `chain_pos` and `path_pos` are arc lengths computed by the Layout during construction.
     pub(crate) fn new(
-        color: Color,
         position: Vec2,
         index: u16,
         segment: u8,
         center_point: Vec2,
+        chain_pos: f32,
+        path_pos: f32,
     ) -> Self {
         let offset = position - center_point;
         let angle = offset.y.atan2(offset.x);
         let distance = offset.length();
-        // let mut angle = direction.angle_between(Vec2::new(1.0, 0.0));
-        // if angle < 0.0 {
-        //     angle += TAU;
-        // }
-        // let distance = position.distance(center_point);
-
-        Led {
-            color,
+
+        LedGeometry {
             position,
             angle,
             distance,
             index,
             segment,
+            chain_pos,
+            path_pos,
+            tangent: Vec2::ZERO,
+            normal: Vec2::ZERO,
+            uv: Vec2::ZERO,
+            square_uv: Vec2::ZERO,
+            normalized_distance: 0.0,
+        }
+    }
+
+    pub(crate) fn set_orientation(&mut self, tangent: Vec2, normal: Vec2) {
+        self.tangent = tangent;
+        self.normal = normal;
+    }
+
+    pub(crate) fn set_normalized_coords(
+        &mut self,
+        uv: Vec2,
+        square_uv: Vec2,
+        normalized_distance: f32,
+    ) {
+        self.uv = uv;
+        self.square_uv = square_uv;
+        self.normalized_distance = normalized_distance;
+    }
+
+    pub(crate) fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub(crate) fn direction(&self) -> Vec2 {
+        Vec2::new(self.angle.cos(), self.angle.sin())
+    }
+
+    pub(crate) fn angle(&self) -> f32 {
+        self.angle
+    }
+
+    pub(crate) fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub(crate) fn uv(&self) -> Vec2 {
+        self.uv
+    }
+
+    pub(crate) fn square_uv(&self) -> Vec2 {
+        self.square_uv
+    }
+
+    pub(crate) fn normalized_distance(&self) -> f32 {
+        self.normalized_distance
+    }
+
+    pub(crate) fn normalized_angle(&self) -> f32 {
+        let turns = self.angle / TAU;
+        if turns < 0.0 {
+            turns + 1.0
+        } else {
+            turns
         }
     }
 
+    pub(crate) fn index(&self) -> u16 {
+        self.index
+    }
+
+    pub(crate) fn segment(&self) -> u8 {
+        self.segment
+    }
+
+    pub(crate) fn tangent(&self) -> Vec2 {
+        self.tangent
+    }
+
+    pub(crate) fn normal(&self) -> Vec2 {
+        self.normal
+    }
+
+    pub(crate) fn chain_pos(&self) -> f32 {
+        self.chain_pos
+    }
+
+    pub(crate) fn path_pos(&self) -> f32 {
+        self.path_pos
+    }
+}
+
+#[derive(Copy, Clone)]
+/// An LED in our Sled configuration, representing both the color of the LED as well as it's spatial information.
+///
+/// Spatial information lives in the Sled's shared [Layout](crate::Layout); an Led is a lightweight view pairing one entry of it with a color.
+pub struct Led<'a, Color: ColorType> {
+    pub color: Color,
+    geometry: &'a LedGeometry,
+}
+
+/// *All properties listed below are pre-calculated on construction;
+/// there is no substantial overhead for calling these methods.*
+impl<'a, Color: ColorType> Led<'a, Color> {
+    /// Pairs a color with the spatial information of an LED.
+    pub(crate) fn new(color: Color, geometry: &'a LedGeometry) -> Self {
+        Led { color, geometry }
+    }
+
     /// Returns the position of the Led in world space.
     pub fn position(&self) -> Vec2 {
-        self.position
+        self.geometry.position()
     }
 
     /// Returns the direction from the Sled's `center_point` to this Led. A normalized vector.
     pub fn direction(&self) -> Vec2 {
-        Vec2::new(self.angle.cos(), self.angle.sin())
+        self.geometry.direction()
     }
 
     /// Returns the angle from the Sled's `center_point` to this Led in radians.
     /// The direction `(1, 0)` is considered 0 radians, `(0, -1)` is pi/2 radian.
     pub fn angle(&self) -> f32 {
-        self.angle
+        self.geometry.angle()
     }
 
     /// Returns the distance from the Sled's `center_point` to this Led.
     pub fn distance(&self) -> f32 {
-        self.distance
+        self.geometry.distance()
+    }
+
+    /// Returns the position of the Led within the Sled's [domain](crate::Sled::domain), where `(0, 0)` is the bottom left corner and `(1, 1)` is the top right.
+    ///
+    /// Each axis is stretched independently to fill the 0-1 range. If you need circles to stay circular, use [Led::square_uv()] instead.
+    pub fn uv(&self) -> Vec2 {
+        self.geometry.uv()
+    }
+
+    /// Like [Led::uv()], but preserves the aspect ratio of the layout.
+    ///
+    /// The longer side of the Sled's domain spans 0 to 1, and the shorter side is centered within that range.
+    pub fn square_uv(&self) -> Vec2 {
+        self.geometry.square_uv()
+    }
+
+    /// Returns the distance from the Sled's `center_point` to this Led, divided by the distance to the [furthest](crate::Sled::furthest) Led. Ranges from 0 to 1.
+    pub fn normalized_distance(&self) -> f32 {
+        self.geometry.normalized_distance()
+    }
+
+    /// Returns the angle from the Sled's `center_point` to this Led as a fraction of a full turn. Ranges from 0 to 1.
+    /// The direction `(1, 0)` is 0, and values increase counter-clockwise.
+    pub fn normalized_angle(&self) -> f32 {
+        self.geometry.normalized_angle()
     }
 
     /// Returns the index of the Led, keeping in mind that Leds in a Sled are treated in memory as one continuous strip.
     pub fn index(&self) -> u16 {
-        self.index
+        self.geometry.index()
     }
 
     /// Returns the index of the LineSegment this Led belongs to.
     pub fn segment(&self) -> u8 {
-        self.segment
+        self.geometry.segment()
+    }
+
+    /// Returns the direction the strip runs in at this Led. A normalized vector.
+    ///
+    /// This matches the direction of the Led's line segment, except within one LED's spacing of a corner, where it blends towards the direction of the next segment so that curved layouts read smoothly.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Vec2};
+    /// // a right-angle corner at (1, 0), with 10 LEDs per segment
+    /// let sled = Sled::<f32>::new_from_str(
+    ///     "center: (0, 1)
+    ///     density: 10
+    ///     --segments--
+    ///     (0, 0) --> (1, 0) --> (1, 1)",
+    /// )
+    /// .unwrap();
+    ///
+    /// // away from the corner, the tangent follows the segment
+    /// assert!(sled.get(0).unwrap().tangent().distance(Vec2::X) < 1e-5);
+    /// // the LED sitting on the corner points halfway between both segments
+    /// let diagonal = Vec2::new(1.0, 1.0).normalize();
+    /// assert!(sled.get(9).unwrap().tangent().distance(diagonal) < 1e-5);
+    /// ```
+    pub fn tangent(&self) -> Vec2 {
+        self.geometry.tangent()
+    }
+
+    /// Returns the direction the strip faces at this Led. A normalized vector perpendicular to the [tangent](Led::tangent), pointing away from the Sled's `center_point`.
+    ///
+    /// ```rust
+    /// # use spatial_led::Sled;
+    /// # let sled = Sled::<f32>::new("./benches/config.yap").unwrap();
+    /// for led in sled.leds() {
+    ///     assert!((led.normal().length() - 1.0).abs() < 1e-5);
+    ///     assert!(led.normal().dot(led.tangent()).abs() < 1e-5);
+    /// }
+    /// ```
+    pub fn normal(&self) -> Vec2 {
+        self.geometry.normal()
+    }
+
+    /// Returns the distance travelled along this Led's chain of connected line segments to reach it, starting from the chain's first vertex.
+    ///
+    /// Chains are the runs of segments joined by `-->` in the config file; a `|` starts a new chain.
+    pub fn chain_pos(&self) -> f32 {
+        self.geometry.chain_pos()
+    }
+
+    /// Returns the distance travelled along every line segment in the Sled to reach this Led, starting from the first vertex.
+    ///
+    /// Gaps between chains are not counted; the segments are measured as if laid end to end.
+    pub fn path_pos(&self) -> f32 {
+        self.geometry.path_pos()
     }
 }
 
-impl<Color: ColorType> PartialEq for Led<Color> {
+impl<Color: ColorType> PartialEq for Led<'_, Color> {
     fn eq(&self, other: &Self) -> bool {
         self.index() == other.index()
     }
 }
 
-impl<Color: ColorType> Eq for Led<Color> {}
+impl<Color: ColorType> Eq for Led<'_, Color> {}
 
-impl<Color: ColorType> PartialOrd for Led<Color> {
+impl<Color: ColorType> PartialOrd for Led<'_, Color> {
     fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl<Color: ColorType> Ord for Led<Color> {
+impl<Color: ColorType> Ord for Led<'_, Color> {
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
-        self.index.cmp(&other.index())
+        self.geometry.index.cmp(&other.index())
     }
 }
 
-impl<Color: ColorType> core::hash::Hash for Led<Color> {
+impl<Color: ColorType> core::hash::Hash for Led<'_, Color> {
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
-        self.index.hash(state);
+        self.geometry.index.hash(state);
     }
 }
 
-impl<Color: ColorType> core::fmt::Debug for Led<Color> {
+impl<Color: ColorType> core::fmt::Debug for Led<'_, Color> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let dir = self.direction();
         f.debug_struct("Led")
             .field("color", &self.color)
-            .field("position", &(self.position.x, self.position.y))
+            .field(
+                "position",
+                &(self.geometry.position.x, self.geometry.position.y),
+            )
             .field("direction", &(dir.x, dir.y))
-            .field("angle", &self.angle)
-            .field("distance", &self.distance)
-            .field("index", &self.index)
-            .field("segment", &self.segment)
+            .field("angle", &self.geometry.angle)
+            .field("distance", &self.geometry.distance)
+            .field("index", &self.geometry.index)
+            .field("segment", &self.geometry.segment)
+            .field("chain_pos", &self.geometry.chain_pos)
+            .field("path_pos", &self.geometry.path_pos)
+            .field(
+                "tangent",
+                &(self.geometry.tangent.x, self.geometry.tangent.y),
+            )
+            .field("normal", &(self.geometry.normal.x, self.geometry.normal.y))
+            .field("uv", &(self.geometry.uv.x, self.geometry.uv.y))
+            .field(
+                "square_uv",
+                &(self.geometry.square_uv.x, self.geometry.square_uv.y),
+            )
+            .field("normalized_distance", &self.geometry.normalized_distance)
             .finish()
     }
 }
 
-impl<Color: ColorType> core::fmt::Display for Led<Color> {
+impl<Color: ColorType> core::fmt::Display for Led<'_, Color> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{}: {:?}", self.index, self.color)
+        write!(f, "{}: {:?}", self.geometry.index, self.color)
     }
 }
@@ -36,8 +36,7 @@
 //!
 //! To [create](Sled::new) a [Sled] struct, you need to create a configuration file and provide its path to the constructor.
 //! ```rust, ignore
-//! use spatial_led::Sled;
-//! use palette::rgb::Rgb;
+//! use spatial_led::{Sled, color::Rgb}; // requires the `palette` feature
 //! let mut sled = Sled::<Rgb>::new("/path/to/config.yap")?;
 //! ```
 //!
@@ -508,15 +507,17 @@
 //!
 //! Opt-in:
 //! - `libm` : Needed for some `no_std` environments.
+//! - `palette` : Re-exports [palette](https://crates.io/crates/palette) through the [color] module, and implements Sled's color traits, like [Blend](color::Blend) and [OutputColor](output::OutputColor), for its color types.
 //! - `core-simd` (Nightly) : Enables portable SIMD support for use by glam.
 //! </details>
 
 extern crate alloc;
-/// Exposes [palette](https://crates.io/crates/palette)'s color management tools and brings the Rgb struct forward for easier use in Sled projects.
+/// Traits for blending colors, plus (behind the `palette` feature flag) [palette](https://crates.io/crates/palette)'s color management tools, with its Rgb struct brought forward for easier use in Sled projects.
 pub mod color;
 mod config;
 mod error;
 mod led;
+mod rect;
 mod spatial_led;
 
 /// Useful tools for building more complicated, time-based visual effects.
@@ -531,13 +532,36 @@ pub mod driver;
 /// Scheduler is an optional feature that can be disabled by turning off the `scheduler` feature flag.
 pub mod scheduler;
 
+pub mod propagation;
+
+/// Named color buffers that can be drawn to independently and composited onto a Sled.
+pub mod layers;
+
+pub mod output;
+
+pub mod lut;
+
+pub mod power;
+
+pub mod gradient;
+
 pub use error::SledError;
 /// Equivalent to `Result<(), SledError>`
 pub type SledResult = Result<(), SledError>;
 /// Using [glam](https://crates.io/crates/glam)'s implementation.
 pub use glam::Vec2;
 pub use led::Led;
+pub use rect::Rect;
+pub use spatial_led::BlurKernel;
 pub use spatial_led::Filter;
+pub use spatial_led::Junction;
+pub use spatial_led::KernelShape;
+pub use spatial_led::Layout;
+pub use spatial_led::Mask;
+pub use spatial_led::Neighborhood;
+pub use spatial_led::PathField;
+pub use spatial_led::RayHit;
 pub use spatial_led::Sled;
+pub use spatial_led::{FilterIntoIter, FilterIter};
 
 pub mod time;
@@ -0,0 +1,306 @@
+//! Color grading with 3D lookup tables.
+//!
+//! A [Lut3d] maps every input color to an output color by sampling a cube of precomputed colors, the same way video and photo tools apply a "look".
+//! Tables are usually loaded from the `.cube` format most grading software exports, and applied to a Sled's colors by an [OutputEncoder](crate::output::OutputEncoder).
+//!
+//! Once a table is loaded, applying it never allocates.
+//!
+//! ```rust
+//! # use spatial_led::Sled;
+//! use spatial_led::{lut::Lut3d, output::OutputEncoder};
+//! # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+//! // a tiny "invert" grade
+//! let cube = "
+//! TITLE \"invert\"
+//! LUT_3D_SIZE 2
+//! 1 1 1
+//! 0 1 1
+//! 1 0 1
+//! 0 0 1
+//! 1 1 0
+//! 0 1 0
+//! 1 0 0
+//! 0 0 0
+//! ";
+//! let lut = Lut3d::from_cube_str(cube).unwrap();
+//!
+//! let mut encoder = OutputEncoder::default();
+//! encoder.set_lut(Some(lut));
+//!
+//! sled.set_all((1.0, 0.0, 0.0));
+//! assert_eq!(&encoder.encode(&sled)[0..3], &[0, 255, 255]);
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::float::Float as _;
+
+use crate::SledError;
+
+/// The largest `LUT_3D_SIZE` the `.cube` specification allows.
+const MAX_CUBE_SIZE: usize = 256;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+/// How a [Lut3d] blends between the entries of its table.
+pub enum LutInterpolation {
+    /// Blends the eight surrounding entries. Smooth and widely supported.
+    #[default]
+    Trilinear,
+    /// Blends the four entries of the tetrahedron the color falls in. Cheaper than trilinear, and better at preserving neutral grays.
+    Tetrahedral,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A 3D lookup table mapping red, green and blue input colors to output colors. See the [module-level docs](crate::lut) for an overview.
+pub struct Lut3d {
+    title: Option<String>,
+    size: usize,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    table: Vec<[f32; 3]>,
+    interpolation: LutInterpolation,
+}
+
+impl Lut3d {
+    /// Creates a Lut3d with `size` entries along each axis, from a table of `size * size * size` colors.
+    ///
+    /// Entries are ordered with red changing fastest, then green, then blue, as in the `.cube` format.
+    /// Returns an [error](SledError) if `size` is less than 2 or the table holds the wrong number of entries.
+    pub fn from_table(size: usize, table: Vec<[f32; 3]>) -> Result<Self, SledError> {
+        if size < 2 {
+            return SledError::new(format!(
+                "A 3D LUT needs at least 2 entries per axis, but {} were given.",
+                size
+            ))
+            .as_err();
+        }
+
+        let entries = size.checked_pow(3);
+        if entries != Some(table.len()) {
+            return SledError::new(format!(
+                "A 3D LUT of size {} needs {}^3 entries, but {} were given.",
+                size,
+                size,
+                table.len()
+            ))
+            .as_err();
+        }
+
+        Ok(Lut3d {
+            title: None,
+            size,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            table,
+            interpolation: LutInterpolation::default(),
+        })
+    }
+
+    /// Creates a Lut3d of the given size that leaves colors untouched. Handy as a starting point for building tables in code.
+    ///
+    /// Returns an [error](SledError) if `size` is less than 2.
+    pub fn identity(size: usize) -> Result<Self, SledError> {
+        let max = size.saturating_sub(1).max(1) as f32;
+        let table = (0..size * size * size)
+            .map(|i| [i % size, (i / size) % size, i / (size * size)].map(|c| c as f32 / max))
+            .collect();
+        Lut3d::from_table(size, table)
+    }
+
+    /// Parses a Lut3d from the contents of a `.cube` file.
+    ///
+    /// Supports the `TITLE`, `LUT_3D_SIZE`, `DOMAIN_MIN` and `DOMAIN_MAX` keywords, along with `#` comments. Returns an [error](SledError) if the file is malformed, describes a 1D LUT, or declares a size above the format's maximum of 256.
+    ///
+    /// ```rust
+    /// use spatial_led::lut::Lut3d;
+    /// assert!(Lut3d::from_cube_str("LUT_3D_SIZE 2000000").is_err());
+    /// ```
+    pub fn from_cube_str(string: &str) -> Result<Self, SledError> {
+        let mut title = None;
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+
+        for (line_number, line) in string.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: &str| {
+                SledError::new(format!(
+                    "Error parsing .cube file on line {}: {}",
+                    line_number + 1,
+                    message
+                ))
+            };
+
+            let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            match keyword {
+                "TITLE" => title = Some(rest.trim().trim_matches('"').to_string()),
+                "LUT_3D_SIZE" => {
+                    let parsed = rest.trim().parse::<usize>();
+                    let parsed = parsed.map_err(|_| error("malformed LUT_3D_SIZE."))?;
+                    if parsed > MAX_CUBE_SIZE {
+                        return Err(error(&format!(
+                            "LUT_3D_SIZE may be at most {}.",
+                            MAX_CUBE_SIZE
+                        )));
+                    }
+                    size = Some(parsed);
+                }
+                "LUT_1D_SIZE" => return Err(error("1D LUTs are not supported.")),
+                "DOMAIN_MIN" => {
+                    domain_min =
+                        parse_triplet(rest).ok_or_else(|| error("malformed DOMAIN_MIN."))?
+                }
+                "DOMAIN_MAX" => {
+                    domain_max =
+                        parse_triplet(rest).ok_or_else(|| error("malformed DOMAIN_MAX."))?
+                }
+                // other keywords, like LUT_IN_VIDEO_RANGE, don't affect how the table is sampled
+                _ if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {}
+                _ => table.push(parse_triplet(line).ok_or_else(|| error("malformed entry."))?),
+            }
+        }
+
+        let Some(size) = size else {
+            return SledError::new(
+                "Error parsing .cube file: no LUT_3D_SIZE descriptor found.".to_string(),
+            )
+            .as_err();
+        };
+
+        let mut lut = Lut3d::from_table(size, table)?;
+        lut.title = title;
+        lut.domain_min = domain_min;
+        lut.domain_max = domain_max;
+        Ok(lut)
+    }
+
+    /// Reads and parses a `.cube` file from disk. See [Lut3d::from_cube_str()].
+    #[cfg(feature = "std")]
+    pub fn from_cube_file(path: &str) -> Result<Self, SledError> {
+        let as_string = std::fs::read_to_string(path).map_err(SledError::from_error)?;
+        Lut3d::from_cube_str(&as_string)
+    }
+
+    /// Returns the title declared in the `.cube` file, if there was one.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the number of entries along each axis of the table.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the input colors mapped to the first and last entries along each axis. Defaults to 0 and 1.
+    pub fn domain(&self) -> ([f32; 3], [f32; 3]) {
+        (self.domain_min, self.domain_max)
+    }
+
+    /// Returns how the table is blended between entries.
+    pub fn interpolation(&self) -> LutInterpolation {
+        self.interpolation
+    }
+
+    /// Sets how the table is blended between entries.
+    pub fn set_interpolation(&mut self, interpolation: LutInterpolation) {
+        self.interpolation = interpolation;
+    }
+
+    fn entry(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        self.table[r + self.size * (g + self.size * b)]
+    }
+
+    /// Looks up the graded color for `rgb`. Inputs outside the table's [domain](Lut3d::domain) are clamped to it.
+    ///
+    /// O(1)
+    ///
+    /// ```rust
+    /// use spatial_led::lut::{Lut3d, LutInterpolation};
+    /// let mut lut = Lut3d::identity(17).unwrap();
+    /// assert_eq!(lut.apply([0.25, 0.5, 0.75]), [0.25, 0.5, 0.75]);
+    ///
+    /// lut.set_interpolation(LutInterpolation::Tetrahedral);
+    /// assert_eq!(lut.apply([0.25, 0.5, 0.75]), [0.25, 0.5, 0.75]);
+    /// ```
+    pub fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let last = (self.size - 1) as f32;
+        let mut base = [0; 3];
+        let mut frac = [0.0; 3];
+        for axis in 0..3 {
+            let span = self.domain_max[axis] - self.domain_min[axis];
+            let t = if span > 0.0 {
+                ((rgb[axis] - self.domain_min[axis]) / span).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            let scaled = t * last;
+            let index = (scaled.floor() as usize).min(self.size - 2);
+            base[axis] = index;
+            frac[axis] = scaled - index as f32;
+        }
+
+        let [r, g, b] = base;
+        let corner = |dr: usize, dg: usize, db: usize| self.entry(r + dr, g + dg, b + db);
+        let [fr, fg, fb] = frac;
+
+        match self.interpolation {
+            LutInterpolation::Trilinear => {
+                let lerp =
+                    |a: [f32; 3], b: [f32; 3], t: f32| [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t);
+                let c00 = lerp(corner(0, 0, 0), corner(1, 0, 0), fr);
+                let c10 = lerp(corner(0, 1, 0), corner(1, 1, 0), fr);
+                let c01 = lerp(corner(0, 0, 1), corner(1, 0, 1), fr);
+                let c11 = lerp(corner(0, 1, 1), corner(1, 1, 1), fr);
+                lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb)
+            }
+            LutInterpolation::Tetrahedral => {
+                // walk from the black corner to the white one along the three largest fractions, in order
+                let (first, second, third, steps) = if fr > fg {
+                    if fg > fb {
+                        (fr, fg, fb, [(1, 0, 0), (1, 1, 0)])
+                    } else if fr > fb {
+                        (fr, fb, fg, [(1, 0, 0), (1, 0, 1)])
+                    } else {
+                        (fb, fr, fg, [(0, 0, 1), (1, 0, 1)])
+                    }
+                } else if fb > fg {
+                    (fb, fg, fr, [(0, 0, 1), (0, 1, 1)])
+                } else if fb > fr {
+                    (fg, fb, fr, [(0, 1, 0), (0, 1, 1)])
+                } else {
+                    (fg, fr, fb, [(0, 1, 0), (1, 1, 0)])
+                };
+
+                let c000 = corner(0, 0, 0);
+                let c_a = corner(steps[0].0, steps[0].1, steps[0].2);
+                let c_b = corner(steps[1].0, steps[1].1, steps[1].2);
+                let c111 = corner(1, 1, 1);
+                [0, 1, 2].map(|i| {
+                    c000[i]
+                        + first * (c_a[i] - c000[i])
+                        + second * (c_b[i] - c_a[i])
+                        + third * (c111[i] - c_b[i])
+                })
+            }
+        }
+    }
+}
+
+fn parse_triplet(s: &str) -> Option<[f32; 3]> {
+    let mut values = s.split_whitespace().map(|v| v.parse::<f32>());
+    let triplet = [
+        values.next()?.ok()?,
+        values.next()?.ok()?,
+        values.next()?.ok()?,
+    ];
+    values.next().is_none().then_some(triplet)
+}
@@ -0,0 +1,782 @@
+//! Turns a [Sled's](Sled) colors into the raw bytes LED hardware expects.
+//!
+//! An [OutputEncoder] applies a gamma curve and global brightness to each color, then writes its channels in the order your strips want them ([ChannelOrder]), at 8 or 16 bits per channel ([BitDepth]).
+//! Slow fades at low brightness can be smoothed out with [temporal dithering](OutputEncoder::set_dithering).
+//! Strips with a dedicated white LED can have it derived from RGB colors using a [WhiteExtraction] strategy.
+//! The encoder owns a byte buffer that is reused from frame to frame, so once it has grown to fit your Sled, encoding never allocates.
+//!
+//! ```rust
+//! # use spatial_led::Sled;
+//! use spatial_led::output::{BitDepth, ChannelOrder, OutputEncoder};
+//! # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+//! let mut encoder = OutputEncoder::new(ChannelOrder::Grb);
+//! encoder.set_gamma(2.2);
+//! encoder.set_brightness(0.6);
+//!
+//! sled.set_all((1.0, 0.5, 0.0));
+//! let bytes: &[u8] = encoder.encode(&sled);
+//! assert_eq!(bytes.len(), sled.num_leds() * 3);
+//! // send bytes to your hardware
+//! ```
+
+use alloc::format;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::float::Float as _;
+
+use crate::{color::ColorType, lut::Lut3d, Sled, SledError};
+
+/// Lets a color type be [encoded](OutputEncoder) into bytes for LED hardware.
+///
+/// Implemented for `f32` (as a shade of gray), RGB and RGBW tuples and arrays of `f32`, and RGB tuples and arrays of `u8`.
+/// Channel values are expected to lie between 0 and 1; anything outside that range is clamped during encoding.
+pub trait OutputColor: Copy {
+    /// Returns the red, green and blue channels of the color.
+    fn rgb(self) -> [f32; 3];
+
+    /// Returns the white channel of the color, if it has one. Defaults to None.
+    fn white(self) -> Option<f32> {
+        None
+    }
+}
+
+impl OutputColor for f32 {
+    fn rgb(self) -> [f32; 3] {
+        [self; 3]
+    }
+}
+
+impl OutputColor for (f32, f32, f32) {
+    fn rgb(self) -> [f32; 3] {
+        [self.0, self.1, self.2]
+    }
+}
+
+impl OutputColor for [f32; 3] {
+    fn rgb(self) -> [f32; 3] {
+        self
+    }
+}
+
+impl OutputColor for (f32, f32, f32, f32) {
+    fn rgb(self) -> [f32; 3] {
+        [self.0, self.1, self.2]
+    }
+
+    fn white(self) -> Option<f32> {
+        Some(self.3)
+    }
+}
+
+impl OutputColor for [f32; 4] {
+    fn rgb(self) -> [f32; 3] {
+        [self[0], self[1], self[2]]
+    }
+
+    fn white(self) -> Option<f32> {
+        Some(self[3])
+    }
+}
+
+impl OutputColor for (u8, u8, u8) {
+    fn rgb(self) -> [f32; 3] {
+        [self.0, self.1, self.2].map(|c| c as f32 / 255.0)
+    }
+}
+
+impl OutputColor for [u8; 3] {
+    fn rgb(self) -> [f32; 3] {
+        self.map(|c| c as f32 / 255.0)
+    }
+}
+
+#[cfg(feature = "palette")]
+mod palette_impls {
+    use super::OutputColor;
+    use palette::{luma::Luma, rgb::Rgb, Alpha};
+
+    impl<S> OutputColor for Rgb<S, f32> {
+        fn rgb(self) -> [f32; 3] {
+            [self.red, self.green, self.blue]
+        }
+    }
+
+    impl<S> OutputColor for Rgb<S, u8> {
+        fn rgb(self) -> [f32; 3] {
+            [self.red, self.green, self.blue].map(|c| c as f32 / 255.0)
+        }
+    }
+
+    impl<S> OutputColor for Luma<S, f32> {
+        fn rgb(self) -> [f32; 3] {
+            [self.luma; 3]
+        }
+    }
+
+    /// Transparent colors are output as if drawn over black: every channel is scaled by alpha.
+    impl<C: OutputColor> OutputColor for Alpha<C, f32> {
+        fn rgb(self) -> [f32; 3] {
+            self.color.rgb().map(|c| c * self.alpha)
+        }
+
+        fn white(self) -> Option<f32> {
+            self.color.white().map(|w| w * self.alpha)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// A single channel of an encoded LED.
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    White,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+/// The order an LED expects to receive its channels in.
+///
+/// WS2812-style strips are usually [ChannelOrder::Grb], and SK6812 RGBW strips [ChannelOrder::Grbw]. If your colors come out swapped, this is the setting to change.
+pub enum ChannelOrder {
+    #[default]
+    Rgb,
+    Rbg,
+    Grb,
+    Gbr,
+    Brg,
+    Bgr,
+    Rgbw,
+    Grbw,
+    Bgrw,
+    Wrgb,
+    Wgrb,
+}
+
+impl ChannelOrder {
+    /// Returns the channels in the order they are written.
+    pub fn channels(self) -> &'static [Channel] {
+        use Channel::*;
+        match self {
+            ChannelOrder::Rgb => &[Red, Green, Blue],
+            ChannelOrder::Rbg => &[Red, Blue, Green],
+            ChannelOrder::Grb => &[Green, Red, Blue],
+            ChannelOrder::Gbr => &[Green, Blue, Red],
+            ChannelOrder::Brg => &[Blue, Red, Green],
+            ChannelOrder::Bgr => &[Blue, Green, Red],
+            ChannelOrder::Rgbw => &[Red, Green, Blue, White],
+            ChannelOrder::Grbw => &[Green, Red, Blue, White],
+            ChannelOrder::Bgrw => &[Blue, Green, Red, White],
+            ChannelOrder::Wrgb => &[White, Red, Green, Blue],
+            ChannelOrder::Wgrb => &[White, Green, Red, Blue],
+        }
+    }
+
+    /// Returns the number of channels per LED.
+    pub fn num_channels(self) -> usize {
+        self.channels().len()
+    }
+
+    /// Returns true if the order includes a white channel.
+    pub fn has_white(self) -> bool {
+        self.channels().contains(&Channel::White)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+/// How many bits each encoded channel takes up.
+pub enum BitDepth {
+    /// One byte per channel, from 0 to 255.
+    #[default]
+    Eight,
+    /// Two bytes per channel, from 0 to 65535, written most significant byte first.
+    Sixteen,
+}
+
+impl BitDepth {
+    /// Returns the number of bytes each channel takes up.
+    pub fn bytes_per_channel(self) -> usize {
+        match self {
+            BitDepth::Eight => 1,
+            BitDepth::Sixteen => 2,
+        }
+    }
+
+    fn max_value(self) -> f32 {
+        match self {
+            BitDepth::Eight => u8::MAX as f32,
+            BitDepth::Sixteen => u16::MAX as f32,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+/// How the white channel of an RGBW LED is derived from an RGB color.
+///
+/// Every strategy moves the neutral part of a color out of the red, green and blue channels and onto the white LED, which is usually brighter and more efficient.
+/// Use one with [OutputEncoder::set_white_extraction()] or [Sled::to_rgbw()].
+///
+/// ```rust
+/// use spatial_led::output::WhiteExtraction;
+/// let rgb = [0.75, 0.5, 0.25];
+/// assert_eq!(WhiteExtraction::None.extract(rgb), [0.75, 0.5, 0.25, 0.0]);
+/// assert_eq!(WhiteExtraction::Min.extract(rgb), [0.5, 0.25, 0.0, 0.25]);
+/// // a white LED twice as bright as red, green and blue combined only needs half the drive
+/// let preserving = WhiteExtraction::LuminancePreserving { white_luminance: 2.0 };
+/// assert_eq!(preserving.extract(rgb), [0.5, 0.25, 0.0, 0.125]);
+/// ```
+pub enum WhiteExtraction {
+    /// Leaves the color untouched and the white channel off.
+    #[default]
+    None,
+    /// Moves the smallest of the three channels onto the white LED.
+    Min,
+    /// Treats the white LED as having the color of a black body at `kelvin` degrees, and extracts as much of that color as possible.
+    ///
+    /// Warm white LEDs (around 3000K) take more of the red channel than the blue one, so colors keep their hue instead of shifting cooler.
+    ColorTemperature { kelvin: f32 },
+    /// Like [WhiteExtraction::Min], but accounts for the white LED being `white_luminance` times as bright as red, green and blue at full drive combined, so overall brightness stays the same.
+    LuminancePreserving { white_luminance: f32 },
+}
+
+impl WhiteExtraction {
+    /// Returns the color the white LED is assumed to emit, normalized so its brightest channel is 1.
+    pub fn white_point(self) -> [f32; 3] {
+        match self {
+            WhiteExtraction::ColorTemperature { kelvin } => kelvin_to_rgb(kelvin),
+            _ => [1.0; 3],
+        }
+    }
+
+    /// Splits `rgb` into red, green, blue and white channels.
+    ///
+    /// For [WhiteExtraction::ColorTemperature], this computes the [white point](WhiteExtraction::white_point) on every call; prefer [OutputEncoder] or [Sled::to_rgbw()] when converting many colors.
+    pub fn extract(self, rgb: [f32; 3]) -> [f32; 4] {
+        self.extract_with(rgb, self.white_point())
+    }
+
+    fn extract_with(self, rgb: [f32; 3], white_point: [f32; 3]) -> [f32; 4] {
+        let [r, g, b] = rgb;
+        let (scale, white) = match self {
+            WhiteExtraction::None => return [r, g, b, 0.0],
+            WhiteExtraction::Min => (1.0, r.min(g).min(b).max(0.0)),
+            WhiteExtraction::ColorTemperature { .. } => {
+                let white = rgb
+                    .iter()
+                    .zip(white_point)
+                    .filter(|(_, w)| *w > 0.0)
+                    .map(|(c, w)| c / w)
+                    .fold(f32::INFINITY, f32::min)
+                    .clamp(0.0, 1.0);
+                let [wr, wg, wb] = white_point.map(|c| c * white);
+                return [r - wr, g - wg, b - wb, white];
+            }
+            WhiteExtraction::LuminancePreserving { white_luminance } => {
+                let scale = white_luminance.max(f32::EPSILON);
+                (scale, (r.min(g).min(b).max(0.0) / scale).min(1.0))
+            }
+        };
+
+        let removed = white * scale;
+        [r - removed, g - removed, b - removed, white]
+    }
+}
+
+/// Passes through colors that already have a white channel, and derives one for those that don't.
+fn split_white(
+    rgb: [f32; 3],
+    white: Option<f32>,
+    white_extraction: WhiteExtraction,
+    white_point: [f32; 3],
+) -> [f32; 4] {
+    let [r, g, b] = rgb;
+    match white {
+        Some(white) => [r, g, b, white],
+        None => white_extraction.extract_with(rgb, white_point),
+    }
+}
+
+/// Approximates the color of a black body at `kelvin` degrees, normalized so its brightest channel is 1.
+///
+/// Based on Tanner Helland's curve fit, which holds between roughly 1000K and 40000K.
+fn kelvin_to_rgb(kelvin: f32) -> [f32; 3] {
+    let temp = kelvin.clamp(1000.0, 40000.0) / 100.0;
+
+    let (r, g) = if temp <= 66.0 {
+        (255.0, 99.470_8 * temp.ln() - 161.119_57)
+    } else {
+        (
+            329.698_73 * (temp - 60.0).powf(-0.133_204_76),
+            288.122_16 * (temp - 60.0).powf(-0.075_514_85),
+        )
+    };
+
+    let b = if temp >= 66.0 {
+        255.0
+    } else if temp <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (temp - 10.0).ln() - 305.044_8
+    };
+
+    let rgb = [r, g, b].map(|c: f32| c.clamp(0.0, 255.0));
+    let max = rgb[0].max(rgb[1]).max(rgb[2]);
+    rgb.map(|c| c / max)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Corrects for differences between LEDs, so that effects can be written in ideal colors and still look consistent across strips from different batches.
+///
+/// A Calibration is a 3x3 matrix applied to each color's red, green and blue channels as it's [encoded](OutputEncoder): after any [color grading](OutputEncoder::set_lut), but ahead of white extraction and gamma correction.
+/// Calibrations are stored per LED on the [Layout](crate::Layout); see [Layout::set_calibration()](crate::Layout::set_calibration).
+///
+/// ```rust
+/// # use spatial_led::Sled;
+/// use spatial_led::output::{Calibration, ChannelOrder, OutputEncoder};
+/// # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+/// // the strip on the third wall runs green
+/// sled.layout_mut()
+///     .set_segment_calibration(2, Calibration::from_gains(1.0, 0.5, 1.0))
+///     .unwrap();
+///
+/// sled.set_all((1.0, 1.0, 1.0));
+/// let mut encoder = OutputEncoder::new(ChannelOrder::Rgb);
+/// let bytes = encoder.encode(&sled);
+///
+/// let first_led_on_wall = sled.segment(2).unwrap().iter().next().unwrap() as usize;
+/// assert_eq!(&bytes[first_led_on_wall * 3..][..3], &[255, 128, 255]);
+/// assert_eq!(&bytes[0..3], &[255, 255, 255]);
+/// ```
+pub struct Calibration {
+    matrix: [[f32; 3]; 3],
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Calibration::IDENTITY
+    }
+}
+
+impl Calibration {
+    /// Leaves colors untouched.
+    pub const IDENTITY: Calibration = Calibration {
+        matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    };
+
+    /// Creates a Calibration that scales each channel independently.
+    pub fn from_gains(red: f32, green: f32, blue: f32) -> Self {
+        Calibration {
+            matrix: [[red, 0.0, 0.0], [0.0, green, 0.0], [0.0, 0.0, blue]],
+        }
+    }
+
+    /// Creates a Calibration from a 3x3 matrix. Each row produces one output channel (red, green, then blue) as a weighted sum of the input's red, green and blue channels.
+    ///
+    /// Useful for correcting hue shifts, where one channel's LED bleeds into another's.
+    pub fn from_matrix(matrix: [[f32; 3]; 3]) -> Self {
+        Calibration { matrix }
+    }
+
+    /// Creates a Calibration from a strip's measured white point: the color it actually shows when every channel is driven at full.
+    ///
+    /// Each channel is scaled down so the strip's white becomes neutral. The weakest channel is left at full, so nothing is pushed out of range.
+    ///
+    /// Channels measured at zero (or not a finite number) can't be corrected, so they're left at full and ignored when picking the weakest channel.
+    ///
+    /// ```rust
+    /// # use spatial_led::output::Calibration;
+    /// // a strip whose red channel is dead
+    /// let calibration = Calibration::from_white_point(0.0, 1.0, 0.5);
+    /// assert_eq!(calibration.apply([1.0, 1.0, 1.0]), [1.0, 0.5, 1.0]);
+    /// ```
+    pub fn from_white_point(red: f32, green: f32, blue: f32) -> Self {
+        let usable = |c: f32| c.is_finite() && c > 0.0;
+        let weakest = [red, green, blue]
+            .into_iter()
+            .filter(|c| usable(*c))
+            .fold(f32::INFINITY, f32::min);
+        let gain = |c: f32| if usable(c) { weakest / c } else { 1.0 };
+        Calibration::from_gains(gain(red), gain(green), gain(blue))
+    }
+
+    /// Returns the Calibration's matrix.
+    pub fn matrix(&self) -> [[f32; 3]; 3] {
+        self.matrix
+    }
+
+    /// Returns a Calibration equivalent to applying this one, followed by `next`.
+    pub fn then(&self, next: &Calibration) -> Calibration {
+        let a = &next.matrix;
+        let b = &self.matrix;
+        Calibration {
+            matrix: core::array::from_fn(|row| {
+                core::array::from_fn(|col| (0..3).map(|k| a[row][k] * b[k][col]).sum())
+            }),
+        }
+    }
+
+    /// Applies the Calibration to a red, green and blue color.
+    pub fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let [r, g, b] = rgb;
+        self.matrix.map(|row| row[0] * r + row[1] * g + row[2] * b)
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Converts a [Sled's](Sled) colors into a byte buffer for LED hardware. See the [module-level docs](crate::output) for an overview.
+///
+/// Each LED's red, green and blue channels are first graded by the encoder's [3D lookup table](OutputEncoder::set_lut) and corrected by the LED's [Calibration], if there are any. Each channel then goes through the same steps: clamped between 0 and 1, raised to the power of the [gamma](OutputEncoder::set_gamma), scaled by the [brightness](OutputEncoder::set_brightness), and finally quantized to the [bit depth](OutputEncoder::set_bit_depth).
+///
+/// If the [ChannelOrder] includes a white channel but the color doesn't have one, white is derived using the encoder's [WhiteExtraction] strategy (by default, it's left off).
+///
+/// ```rust
+/// # use spatial_led::Sled;
+/// use spatial_led::output::{BitDepth, ChannelOrder, OutputEncoder};
+/// # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+/// sled.set_all((1.0, 0.5, 0.0));
+///
+/// let mut encoder = OutputEncoder::new(ChannelOrder::Grb);
+/// assert_eq!(&encoder.encode(&sled)[0..3], &[128, 255, 0]);
+///
+/// encoder.set_bit_depth(BitDepth::Sixteen);
+/// assert_eq!(&encoder.encode(&sled)[0..6], &[128, 0, 255, 255, 0, 0]);
+/// ```
+pub struct OutputEncoder {
+    order: ChannelOrder,
+    gamma: f32,
+    brightness: f32,
+    bit_depth: BitDepth,
+    white_extraction: WhiteExtraction,
+    white_point: [f32; 3],
+    dithering: bool,
+    dither_error: Vec<f32>,
+    lut: Option<Lut3d>,
+    buffer: Vec<u8>,
+}
+
+impl Default for OutputEncoder {
+    fn default() -> Self {
+        OutputEncoder::new(ChannelOrder::default())
+    }
+}
+
+impl OutputEncoder {
+    /// Creates an encoder that writes channels in the given order, with a linear gamma, full brightness and 8 bits per channel.
+    pub fn new(order: ChannelOrder) -> Self {
+        OutputEncoder {
+            order,
+            gamma: 1.0,
+            brightness: 1.0,
+            bit_depth: BitDepth::Eight,
+            white_extraction: WhiteExtraction::None,
+            white_point: [1.0; 3],
+            dithering: false,
+            dither_error: Vec::new(),
+            lut: None,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Returns the order channels are written in.
+    pub fn channel_order(&self) -> ChannelOrder {
+        self.order
+    }
+
+    /// Sets the order channels are written in.
+    pub fn set_channel_order(&mut self, order: ChannelOrder) {
+        self.order = order;
+    }
+
+    /// Returns the gamma exponent applied to each channel.
+    pub fn gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    /// Sets the gamma exponent applied to each channel. 1.0 leaves values untouched; most LEDs look best somewhere between 2.2 and 2.8.
+    pub fn set_gamma(&mut self, gamma: f32) {
+        self.gamma = gamma.max(0.0);
+    }
+
+    /// Returns the global brightness, between 0 and 1.
+    pub fn brightness(&self) -> f32 {
+        self.brightness
+    }
+
+    /// Sets the global brightness. Values are clamped between 0 and 1.
+    ///
+    /// Brightness is applied after the gamma curve, so it scales the light output linearly.
+    pub fn set_brightness(&mut self, brightness: f32) {
+        self.brightness = brightness.clamp(0.0, 1.0);
+    }
+
+    /// Returns the number of bits each channel is encoded with.
+    pub fn bit_depth(&self) -> BitDepth {
+        self.bit_depth
+    }
+
+    /// Sets the number of bits each channel is encoded with.
+    pub fn set_bit_depth(&mut self, bit_depth: BitDepth) {
+        self.bit_depth = bit_depth;
+    }
+
+    /// Returns the strategy used to derive the white channel for colors that don't have one.
+    pub fn white_extraction(&self) -> WhiteExtraction {
+        self.white_extraction
+    }
+
+    /// Sets the strategy used to derive the white channel for colors that don't have one. Only has an effect if the [ChannelOrder] includes a white channel.
+    ///
+    /// ```rust
+    /// # use spatial_led::Sled;
+    /// use spatial_led::output::{ChannelOrder, OutputEncoder, WhiteExtraction};
+    /// # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// let mut encoder = OutputEncoder::new(ChannelOrder::Grbw);
+    /// encoder.set_white_extraction(WhiteExtraction::ColorTemperature { kelvin: 3000.0 });
+    ///
+    /// sled.set_all((1.0, 1.0, 1.0));
+    /// let bytes = encoder.encode(&sled);
+    /// // most of the light now comes from the warm white LED
+    /// assert_eq!(bytes[3], 255);
+    /// ```
+    pub fn set_white_extraction(&mut self, white_extraction: WhiteExtraction) {
+        self.white_extraction = white_extraction;
+        self.white_point = white_extraction.white_point();
+    }
+
+    /// Returns the 3D lookup table colors are graded with, if there is one.
+    pub fn lut(&self) -> Option<&Lut3d> {
+        self.lut.as_ref()
+    }
+
+    /// Sets or clears the 3D lookup table colors are graded with. See [Lut3d].
+    ///
+    /// Grading is the first step of encoding, so the table sees the colors exactly as the effect drew them.
+    pub fn set_lut(&mut self, lut: Option<Lut3d>) {
+        self.lut = lut;
+    }
+
+    /// Returns true if temporal dithering is enabled.
+    pub fn dithering(&self) -> bool {
+        self.dithering
+    }
+
+    /// Enables or disables temporal dithering.
+    ///
+    /// Quantizing to 8 bits leaves only a handful of levels at the dim end of a fade, which shows up as visible steps.
+    /// With dithering enabled, the encoder remembers how far each channel of each LED was rounded off, and carries that error into the next frame.
+    /// A channel meant to sit a quarter of the way between two levels is then driven at the higher one every fourth frame, so that on average it lands where it should.
+    ///
+    /// Dithering works best when frames are encoded and sent at a high rate, for example by running a [Scheduler](crate::scheduler::Scheduler) well above the rate your effect changes at.
+    /// The error is kept for each LED, so encode the same Sled (or Sleds with the same number of LEDs) every frame.
+    ///
+    /// ```rust
+    /// # use spatial_led::Sled;
+    /// use spatial_led::output::OutputEncoder;
+    /// # let mut sled = Sled::<f32>::new("./benches/config.yap").unwrap();
+    /// let mut encoder = OutputEncoder::default();
+    /// encoder.set_dithering(true);
+    ///
+    /// // a quarter of the way between 0 and 1 on an 8-bit scale
+    /// sled.set_all(0.25 / 255.0);
+    /// let lit_frames = (0..4).filter(|_| encoder.encode(&sled)[0] == 1).count();
+    /// assert_eq!(lit_frames, 1);
+    /// ```
+    pub fn set_dithering(&mut self, dithering: bool) {
+        self.dithering = dithering;
+        self.reset_dithering();
+    }
+
+    /// Forgets the rounding error carried over from previous frames.
+    pub fn reset_dithering(&mut self) {
+        self.dither_error.clear();
+    }
+
+    /// Returns the number of bytes each LED takes up once encoded.
+    pub fn bytes_per_led(&self) -> usize {
+        self.order.num_channels() * self.bit_depth.bytes_per_channel()
+    }
+
+    /// Returns the number of bytes needed to encode `num_leds` LEDs.
+    pub fn frame_len(&self, num_leds: usize) -> usize {
+        num_leds * self.bytes_per_led()
+    }
+
+    /// Returns the bytes written by the most recent call to [OutputEncoder::encode()].
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Encodes every LED in `sled` into the encoder's internal buffer and returns it.
+    ///
+    /// The buffer is only reallocated when it needs to grow, so encoding the same Sled frame after frame never allocates.
+    ///
+    /// O(LEDS)
+    pub fn encode<Color: ColorType + OutputColor>(&mut self, sled: &Sled<Color>) -> &[u8] {
+        let mut buffer = core::mem::take(&mut self.buffer);
+        buffer.resize(self.frame_len(sled.num_leds()), 0);
+        self.write_colors(sled, &mut buffer);
+        self.buffer = buffer;
+        &self.buffer
+    }
+
+    /// Encodes every LED in `sled` into `out`, for when you'd rather manage the buffer yourself (a DMA buffer, for example).
+    ///
+    /// Returns the number of bytes written, or an [error](SledError) if `out` is too small to hold [OutputEncoder::frame_len()] bytes.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    /// # use spatial_led::Sled;
+    /// use spatial_led::output::{ChannelOrder, OutputEncoder};
+    /// # let sled = Sled::<(f32, f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// let mut encoder = OutputEncoder::new(ChannelOrder::Grbw);
+    /// let mut frame = [0u8; 4096];
+    /// let written = encoder.encode_into(&sled, &mut frame).unwrap();
+    /// assert_eq!(written, sled.num_leds() * 4);
+    /// ```
+    pub fn encode_into<Color: ColorType + OutputColor>(
+        &mut self,
+        sled: &Sled<Color>,
+        out: &mut [u8],
+    ) -> Result<usize, SledError> {
+        let len = self.frame_len(sled.num_leds());
+        if out.len() < len {
+            return SledError::new(format!(
+                "Output buffer holds {} bytes, but {} are needed to encode {} LEDs.",
+                out.len(),
+                len,
+                sled.num_leds()
+            ))
+            .as_err();
+        }
+
+        self.write_colors(sled, &mut out[..len]);
+        Ok(len)
+    }
+
+    fn write_colors<Color: ColorType + OutputColor>(&mut self, sled: &Sled<Color>, out: &mut [u8]) {
+        let bytes_per_led = self.bytes_per_led();
+        let num_channels = self.order.num_channels();
+        let calibrations = sled.layout().calibrations();
+        let colors = sled
+            .colors()
+            .enumerate()
+            .map(|(i, color)| (*color, calibrations.get(i)));
+
+        if !self.dithering {
+            for ((color, calibration), bytes) in colors.zip(out.chunks_exact_mut(bytes_per_led)) {
+                self.write_color(color, calibration, bytes, &mut []);
+            }
+            return;
+        }
+
+        // a change in size means a different set of LEDs, so the old error no longer applies
+        let error_len = out.len() / bytes_per_led * num_channels;
+        if self.dither_error.len() != error_len {
+            self.dither_error.clear();
+            self.dither_error.resize(error_len, 0.0);
+        }
+
+        let mut dither_error = core::mem::take(&mut self.dither_error);
+        for (((color, calibration), bytes), error) in colors
+            .zip(out.chunks_exact_mut(bytes_per_led))
+            .zip(dither_error.chunks_exact_mut(num_channels))
+        {
+            self.write_color(color, calibration, bytes, error);
+        }
+        self.dither_error = dither_error;
+    }
+
+    /// Writes a single color to `out`. If `error` isn't empty, it holds the dithering error of each channel and is updated in place.
+    fn write_color<Color: OutputColor>(
+        &self,
+        color: Color,
+        calibration: Option<&Calibration>,
+        out: &mut [u8],
+        error: &mut [f32],
+    ) {
+        let white_extraction = if self.order.has_white() {
+            self.white_extraction
+        } else {
+            WhiteExtraction::None
+        };
+
+        let mut rgb = color.rgb();
+        if let Some(lut) = &self.lut {
+            rgb = lut.apply(rgb);
+        }
+        if let Some(calibration) = calibration {
+            rgb = calibration.apply(rgb);
+        }
+        let [r, g, b, w] = split_white(rgb, color.white(), white_extraction, self.white_point);
+        let width = self.bit_depth.bytes_per_channel();
+
+        for (i, (channel, bytes)) in self
+            .order
+            .channels()
+            .iter()
+            .zip(out.chunks_exact_mut(width))
+            .enumerate()
+        {
+            let value = match channel {
+                Channel::Red => r,
+                Channel::Green => g,
+                Channel::Blue => b,
+                Channel::White => w,
+            };
+
+            let level = self.quantize(value, error.get_mut(i));
+            match self.bit_depth {
+                BitDepth::Eight => bytes[0] = level as u8,
+                BitDepth::Sixteen => bytes.copy_from_slice(&(level as u16).to_be_bytes()),
+            }
+        }
+    }
+
+    fn quantize(&self, value: f32, error: Option<&mut f32>) -> u32 {
+        let max = self.bit_depth.max_value();
+        let target = value.clamp(0.0, 1.0).powf(self.gamma) * self.brightness * max;
+
+        match error {
+            Some(error) => {
+                let dithered = target + *error;
+                let level = dithered.round().clamp(0.0, max);
+                *error = dithered - level;
+                level as u32
+            }
+            None => target.round() as u32,
+        }
+    }
+}
+
+/// # Output conversion methods
+impl<Color: ColorType + OutputColor> Sled<Color> {
+    /// Returns a new Sled sharing this one's [Layout](crate::Layout), where each color has been split into red, green, blue and white channels using the given [WhiteExtraction] strategy.
+    ///
+    /// Colors that already have a white channel are copied over unchanged.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    /// # use spatial_led::Sled;
+    /// use spatial_led::output::WhiteExtraction;
+    /// # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// sled.set_all((0.75, 0.5, 0.25));
+    /// let rgbw = sled.to_rgbw(WhiteExtraction::Min);
+    /// assert_eq!(rgbw.get(0).unwrap().color, (0.5, 0.25, 0.0, 0.25));
+    /// ```
+    pub fn to_rgbw(&self, white_extraction: WhiteExtraction) -> Sled<(f32, f32, f32, f32)> {
+        let white_point = white_extraction.white_point();
+        let mut rgbw = Sled::from_layout(Arc::clone(self.layout()));
+        let mut colors = self.colors();
+        rgbw.for_each(|led| {
+            let color = *colors.next().unwrap();
+            let [r, g, b, w] =
+                split_white(color.rgb(), color.white(), white_extraction, white_point);
+            led.color = (r, g, b, w);
+        });
+        rgbw
+    }
+}
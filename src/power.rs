@@ -0,0 +1,279 @@
+//! Estimates how much current a frame will draw, and dims it to stay within what your power supplies can deliver.
+//!
+//! A [PowerModel] describes how much current each channel of an LED draws at full drive, plus the idle current every LED draws even when off.
+//! Supplies can be given a limit for the whole installation, or one per [supply group](PowerModel::add_supply): a [Filter] of the LEDs fed by a particular supply or injection point.
+//!
+//! ```rust
+//! # use spatial_led::Sled;
+//! use spatial_led::power::PowerModel;
+//! # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+//! // a typical WS2812B: ~12mA per channel at full brightness, ~1mA idle
+//! let mut power = PowerModel::new(12.0, 12.0, 12.0);
+//! power.set_idle_ma(1.0);
+//! power.set_global_limit(Some(4000.0));
+//! power.add_supply(sled.segments(0..3).unwrap(), 2000.0);
+//!
+//! sled.set_all((1.0, 1.0, 1.0));
+//! let report = power.limit(&mut sled);
+//! assert!(report.total_ma > 4000.0);
+//! assert!(power.estimate(&sled).total_ma <= 4000.0 + 1e-2);
+//! ```
+
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::float::Float as _;
+
+use crate::{
+    color::{Blend, ColorType},
+    output::OutputColor,
+    Filter, Sled,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+/// A group of LEDs fed by the same power supply or injection point, along with the most current that supply can deliver.
+pub struct Supply {
+    /// The LEDs powered by this supply.
+    pub filter: Filter,
+    /// The most current this supply can deliver, in milliamps.
+    pub limit_ma: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// How much current a [Supply] is estimated to draw in a given frame.
+pub struct SupplyReport {
+    /// The estimated current draw of every LED in the supply group, in milliamps.
+    pub draw_ma: f32,
+    /// The most current the supply can deliver, in milliamps.
+    pub limit_ma: f32,
+    /// The factor the group's light output must be scaled by to stay within its limit. 1 if it's already within budget.
+    pub scale: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// The estimated current draw of a frame, as returned by [PowerModel::estimate()] and [PowerModel::limit()].
+pub struct PowerReport {
+    /// The estimated current draw of every LED in the Sled, in milliamps.
+    pub total_ma: f32,
+    /// The factor the whole frame's light output must be scaled by to stay within the [global limit](PowerModel::set_global_limit). 1 if it's already within budget, or if there is no global limit.
+    ///
+    /// This can be passed straight to [OutputEncoder::set_brightness()](crate::output::OutputEncoder::set_brightness), since encoder brightness is applied after gamma correction.
+    pub scale: f32,
+    /// One report per [Supply], in the order they were added.
+    pub supplies: Vec<SupplyReport>,
+}
+
+impl PowerReport {
+    /// Returns true if the frame draws more current than the global limit or any supply's limit allows.
+    pub fn over_budget(&self) -> bool {
+        self.scale < 1.0 || self.supplies.iter().any(|supply| supply.scale < 1.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Describes the current draw of an LED installation. See the [module-level docs](crate::power) for an overview.
+///
+/// Each LED is estimated to draw its idle current, plus each channel's full-drive current scaled by that channel's level.
+/// Levels are clamped between 0 and 1 and raised to the power of the model's [gamma](PowerModel::set_gamma), which should match the gamma used when [encoding](crate::output::OutputEncoder) the frame.
+pub struct PowerModel {
+    channel_ma: [f32; 4],
+    idle_ma: f32,
+    gamma: f32,
+    global_limit_ma: Option<f32>,
+    supplies: Vec<Supply>,
+}
+
+impl PowerModel {
+    /// Creates a model where the red, green and blue channels draw the given current at full drive, in milliamps.
+    /// There is no white channel, no idle current, a linear gamma and no limits.
+    pub fn new(red_ma: f32, green_ma: f32, blue_ma: f32) -> Self {
+        PowerModel {
+            channel_ma: [red_ma, green_ma, blue_ma, 0.0],
+            idle_ma: 0.0,
+            gamma: 1.0,
+            global_limit_ma: None,
+            supplies: Vec::new(),
+        }
+    }
+
+    /// Returns the current drawn by the red, green, blue and white channels at full drive, in milliamps.
+    pub fn channel_ma(&self) -> [f32; 4] {
+        self.channel_ma
+    }
+
+    /// Sets the current drawn by the white channel at full drive, in milliamps.
+    pub fn set_white_ma(&mut self, white_ma: f32) {
+        self.channel_ma[3] = white_ma;
+    }
+
+    /// Returns the current each LED draws while fully off, in milliamps.
+    pub fn idle_ma(&self) -> f32 {
+        self.idle_ma
+    }
+
+    /// Sets the current each LED draws while fully off, in milliamps.
+    pub fn set_idle_ma(&mut self, idle_ma: f32) {
+        self.idle_ma = idle_ma;
+    }
+
+    /// Returns the gamma exponent applied to each channel before estimating its draw.
+    pub fn gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    /// Sets the gamma exponent applied to each channel before estimating its draw.
+    pub fn set_gamma(&mut self, gamma: f32) {
+        self.gamma = gamma.max(f32::EPSILON);
+    }
+
+    /// Returns the most current the whole installation may draw, in milliamps, if there is a limit.
+    pub fn global_limit(&self) -> Option<f32> {
+        self.global_limit_ma
+    }
+
+    /// Sets or clears the most current the whole installation may draw, in milliamps.
+    pub fn set_global_limit(&mut self, limit_ma: Option<f32>) {
+        self.global_limit_ma = limit_ma;
+    }
+
+    /// Adds a supply group: the LEDs in `filter` are fed by a supply that can deliver at most `limit_ma` milliamps.
+    ///
+    /// Supply groups are expected not to overlap. If they do, LEDs belonging to several groups may be dimmed more than necessary.
+    pub fn add_supply(&mut self, filter: Filter, limit_ma: f32) {
+        self.supplies.push(Supply { filter, limit_ma });
+    }
+
+    /// Returns each supply group, in the order they were added.
+    pub fn supplies(&self) -> &[Supply] {
+        &self.supplies
+    }
+
+    /// Removes every supply group.
+    pub fn clear_supplies(&mut self) {
+        self.supplies.clear();
+    }
+
+    /// Returns the estimated current draw of a single color, in milliamps, not counting idle current.
+    pub fn color_ma<Color: OutputColor>(&self, color: Color) -> f32 {
+        let [r, g, b] = color.rgb();
+        let w = color.white().unwrap_or(0.0);
+
+        [r, g, b, w]
+            .iter()
+            .zip(self.channel_ma)
+            .map(|(level, ma)| level.clamp(0.0, 1.0).powf(self.gamma) * ma)
+            .sum()
+    }
+
+    /// Estimates the current draw of every LED in `sled`, and of each supply group.
+    ///
+    /// O(LEDS)
+    pub fn estimate<Color: ColorType + OutputColor>(&self, sled: &Sled<Color>) -> PowerReport {
+        let active_ma: f32 = sled.colors().map(|color| self.color_ma(*color)).sum();
+        let idle_ma = self.idle_ma * sled.num_leds() as f32;
+
+        let supplies = self
+            .supplies
+            .iter()
+            .map(|supply| {
+                let mut group_active_ma = 0.0;
+                let mut count = 0;
+                for i in &supply.filter {
+                    if let Some(led) = sled.get(i as usize) {
+                        group_active_ma += self.color_ma(led.color);
+                        count += 1;
+                    }
+                }
+
+                let group_idle_ma = self.idle_ma * count as f32;
+                SupplyReport {
+                    draw_ma: group_idle_ma + group_active_ma,
+                    limit_ma: supply.limit_ma,
+                    scale: budget_scale(group_active_ma, group_idle_ma, supply.limit_ma),
+                }
+            })
+            .collect();
+
+        let scale = match self.global_limit_ma {
+            Some(limit_ma) => budget_scale(active_ma, idle_ma, limit_ma),
+            None => 1.0,
+        };
+
+        PowerReport {
+            total_ma: idle_ma + active_ma,
+            scale,
+            supplies,
+        }
+    }
+
+    /// Estimates the current draw of `sled`, then dims any supply group over its limit, followed by the whole Sled if it's still over the global limit.
+    ///
+    /// Returns the estimate from before any dimming took place, with the scales that were applied. Since colors are dimmed before gamma correction, each scale is undone by the model's gamma first.
+    ///
+    /// Channels of dimmed LEDs are clamped between 0 and 1 before scaling, so colors pushed out of range (by [BlendMode::Add](crate::color::BlendMode::Add), say) still end up within budget.
+    ///
+    /// If you'd rather leave the Sled's colors alone and only dim globally, pass [PowerReport::scale] from [PowerModel::estimate()] to your [OutputEncoder](crate::output::OutputEncoder) instead.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    /// # use spatial_led::Sled;
+    /// use spatial_led::power::PowerModel;
+    /// # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// let mut power = PowerModel::new(20.0, 20.0, 20.0);
+    /// power.set_gamma(2.2);
+    /// power.set_global_limit(Some(1000.0));
+    ///
+    /// // overdriven colors, well past full brightness
+    /// sled.set_all((3.0, 2.0, 0.5));
+    /// assert!(power.limit(&mut sled).over_budget());
+    ///
+    /// assert!(power.estimate(&sled).total_ma <= 1000.0 + 1e-2);
+    /// ```
+    pub fn limit<Color: ColorType + OutputColor + Blend>(
+        &self,
+        sled: &mut Sled<Color>,
+    ) -> PowerReport {
+        let mut report = self.estimate(sled);
+
+        let mut dimmed = false;
+        for (supply, supply_report) in self.supplies.iter().zip(&report.supplies) {
+            if supply_report.scale < 1.0 {
+                let factor = supply_report.scale.powf(1.0 / self.gamma);
+                sled.modulate_filter(&supply.filter, |led| dim(led.color, factor));
+                dimmed = true;
+            }
+        }
+
+        if let Some(limit_ma) = self.global_limit_ma {
+            if dimmed {
+                let active_ma: f32 = sled.colors().map(|color| self.color_ma(*color)).sum();
+                let idle_ma = self.idle_ma * sled.num_leds() as f32;
+                report.scale = budget_scale(active_ma, idle_ma, limit_ma);
+            }
+
+            if report.scale < 1.0 {
+                let factor = report.scale.powf(1.0 / self.gamma);
+                sled.map(|led| dim(led.color, factor));
+            }
+        }
+
+        report
+    }
+}
+
+/// The factor `active_ma` must be scaled by so that, together with `idle_ma`, it fits within `limit_ma`.
+fn budget_scale(active_ma: f32, idle_ma: f32, limit_ma: f32) -> f32 {
+    if idle_ma + active_ma <= limit_ma {
+        1.0
+    } else if active_ma > 0.0 {
+        ((limit_ma - idle_ma) / active_ma).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Scales `color` by `factor`, clamping channels into the 0 to 1 range first so they match what [PowerModel::color_ma()] estimates.
+fn dim<Color: Blend>(color: Color, factor: f32) -> Color {
+    color.zip_channels(color, |c, _| c.clamp(0.0, 1.0) * factor)
+}
@@ -0,0 +1,506 @@
+//! Agents are points that travel along the LED strips at a fixed speed, following the [Sled's](Sled) topology.
+//! Whenever an agent reaches a [Junction](crate::Junction), a [Routing] rule decides which segment it continues onto.
+//!
+//! A [Propagator] owns a set of agents, advances them through time, and draws them (with optional trails) onto a Sled.
+//! Since it's just plain data, it can be stored in a Driver's [Data](crate::driver::Data), stepped during compute commands, and drawn during draw commands.
+//!
+//! ```rust
+//! # use spatial_led::{Sled, driver::Driver};
+//! # use palette::rgb::Rgb;
+//! use spatial_led::propagation::{Propagator, Routing};
+//! let mut driver = Driver::<Rgb>::new();
+//!
+//! driver.set_startup_commands(|sled, data| {
+//!     let mut packets = Propagator::new(Routing::RoundRobin);
+//!     // two packets travelling at 3 units per second, leaving 1 unit long trails
+//!     packets.spawn(sled, 0, 3.0, 1.0);
+//!     packets.spawn(sled, 200, -3.0, 1.0);
+//!     data.set("packets", packets);
+//!     Ok(())
+//! });
+//!
+//! driver.set_compute_commands(|sled, data, time| {
+//!     let packets: &mut Propagator = data.get_mut("packets")?;
+//!     packets.step(sled, time.delta);
+//!     Ok(())
+//! });
+//!
+//! driver.set_draw_commands(|sled, data, _| {
+//!     sled.map(|led| led.color * 0.9);
+//!     let packets: &Propagator = data.get("packets")?;
+//!     packets.draw(sled, |led, _agent, alpha| {
+//!         led.color + Rgb::new(1.0, 0.5, 0.1) * (1.0 - alpha)
+//!     });
+//!     Ok(())
+//! });
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use smallvec::{smallvec, SmallVec};
+
+use crate::{color::ColorType, led::Led, Sled, Vec2};
+
+/// Upper bound on junctions crossed by a single agent in one step, guarding against layouts of zero-length segments.
+const MAX_HOPS_PER_STEP: usize = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Decides which segment an [Agent] continues onto when it reaches a [Junction](crate::Junction).
+///
+/// The segment the agent arrived on is never chosen unless it hits a dead end. See [DeadEnd].
+pub enum Routing {
+    /// Pick one of the other segments at random.
+    Random,
+    /// Cycle through the other segments in order, one agent at a time.
+    RoundRobin,
+    /// Take the sharpest counter-clockwise turn.
+    Left,
+    /// Take the sharpest clockwise turn.
+    Right,
+    /// Take whichever segment deviates least from the current heading.
+    Straight,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+/// What an [Agent] does when it reaches a [Junction](crate::Junction) with no other segments to continue onto.
+pub enum DeadEnd {
+    /// Turn around and travel back the way it came.
+    #[default]
+    Reverse,
+    /// Remove the agent from its [Propagator].
+    Remove,
+}
+
+#[derive(Clone, Debug)]
+/// A point travelling along the LED strips, managed by a [Propagator].
+pub struct Agent {
+    id: u32,
+    segment: usize,
+    // distance from the start of `segment`
+    t: f32,
+    // true if travelling from the segment's start towards its end
+    forward: bool,
+    speed: f32,
+    trail_length: f32,
+    // previously visited (segment, forward) pairs, most recent last. Only kept as long as the trail needs them.
+    history: SmallVec<[(usize, bool); 4]>,
+}
+
+impl Agent {
+    /// Returns the identifier assigned to this agent by [Propagator::spawn()].
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns the index of the line segment this agent is currently travelling along.
+    pub fn segment(&self) -> usize {
+        self.segment
+    }
+
+    /// Returns the speed of this agent, in units per second.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Sets the speed of this agent, in units per second.
+    ///
+    /// As with [Propagator::spawn()], the sign sets the direction of travel: positive speeds travel towards the end of the agent's current line segment, negative speeds towards its start.
+    pub fn set_speed(&mut self, speed: f32) {
+        let forward = speed >= 0.0;
+        if forward != self.forward {
+            self.forward = forward;
+            self.history.clear();
+        }
+        self.speed = speed.abs();
+    }
+
+    /// Returns the length of the trail drawn behind this agent.
+    pub fn trail_length(&self) -> f32 {
+        self.trail_length
+    }
+
+    /// Sets the length of the trail drawn behind this agent.
+    ///
+    /// Trails can only extend back as far as the agent has already travelled, so lengthening one takes effect gradually.
+    pub fn set_trail_length(&mut self, trail_length: f32) {
+        self.trail_length = trail_length.max(0.0);
+    }
+
+    /// Returns the position of this agent in world space.
+    pub fn position<Color: ColorType>(&self, sled: &Sled<Color>) -> Vec2 {
+        let segment = sled.line_segment(self.segment);
+        let length = segment.length();
+        if length > 0.0 {
+            segment.start.lerp(segment.end, self.t / length)
+        } else {
+            segment.start
+        }
+    }
+
+    /// Returns the normalized direction this agent is travelling in.
+    pub fn heading<Color: ColorType>(&self, sled: &Sled<Color>) -> Vec2 {
+        heading(sled, self.segment, self.forward)
+    }
+}
+
+fn heading<Color: ColorType>(sled: &Sled<Color>, segment_index: usize, forward: bool) -> Vec2 {
+    let segment = sled.line_segment(segment_index);
+    let dir = (segment.end - segment.start).normalize_or_zero();
+    if forward {
+        dir
+    } else {
+        -dir
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Owns a set of [Agents](Agent) and moves them along a [Sled's](Sled) topology. See the [module-level documentation](crate::propagation) for an example.
+///
+/// Here, agents climb the stem of a T-junction and turn according to the Propagator's [Routing]:
+///
+/// ```rust
+/// # use spatial_led::{Sled, Vec2};
+/// use spatial_led::propagation::{Propagator, Routing};
+/// use std::time::Duration;
+///
+/// // segment 0 runs west to the junction, 1 runs east from it, and 2 is the stem below
+/// let sled = Sled::<f32>::new_from_str(
+///     "center: (0, 0)
+///     density: 10
+///     --segments--
+///     (-1, 0) --> (0, 0) --> (1, 0) |
+///     (0, -1) --> (0, 0)",
+/// )
+/// .unwrap();
+/// let stem_start = 20; // first LED on the stem, at (0, -0.9)
+///
+/// let mut left = Propagator::new(Routing::Left);
+/// left.spawn(&sled, stem_start, 1.0, 0.0);
+/// left.step(&sled, Duration::from_millis(1400));
+/// let agent = left.agents().next().unwrap();
+/// assert_eq!(agent.segment(), 0);
+/// assert!(agent.position(&sled).distance(Vec2::new(-0.5, 0.0)) < 1e-4);
+///
+/// let mut right = Propagator::new(Routing::Right);
+/// right.spawn(&sled, stem_start, 1.0, 0.0);
+/// right.step(&sled, Duration::from_millis(1400));
+/// let agent = right.agents().next().unwrap();
+/// assert_eq!(agent.segment(), 1);
+/// assert!(agent.position(&sled).distance(Vec2::new(0.5, 0.0)) < 1e-4);
+///
+/// // round robin alternates between the other segments, one agent at a time
+/// let mut round_robin = Propagator::new(Routing::RoundRobin);
+/// round_robin.spawn(&sled, stem_start, 1.0, 0.0);
+/// round_robin.spawn(&sled, stem_start, 1.0, 0.0);
+/// round_robin.step(&sled, Duration::from_millis(1400));
+/// let segments: Vec<usize> = round_robin.agents().map(|agent| agent.segment()).collect();
+/// assert_eq!(segments, [0, 1]);
+/// ```
+pub struct Propagator {
+    agents: Vec<Agent>,
+    routing: Routing,
+    junction_routing: BTreeMap<usize, Routing>,
+    round_robin_counters: BTreeMap<usize, usize>,
+    dead_end: DeadEnd,
+    rng_state: u32,
+    next_id: u32,
+}
+
+impl Propagator {
+    /// Constructs a new Propagator with no agents, using `routing` at every [Junction](crate::Junction).
+    pub fn new(routing: Routing) -> Self {
+        Propagator {
+            agents: Vec::new(),
+            routing,
+            junction_routing: BTreeMap::new(),
+            round_robin_counters: BTreeMap::new(),
+            dead_end: DeadEnd::default(),
+            rng_state: 0x9E37_79B9,
+            next_id: 0,
+        }
+    }
+
+    /// Changes the routing rule used at every [Junction](crate::Junction) without an override.
+    pub fn set_routing(&mut self, routing: Routing) {
+        self.routing = routing;
+    }
+
+    /// Overrides the routing rule used at the junction with the given [index](crate::Junction::index).
+    pub fn set_junction_routing(&mut self, junction_index: usize, routing: Routing) {
+        self.junction_routing.insert(junction_index, routing);
+    }
+
+    /// Removes any routing override for the junction with the given [index](crate::Junction::index).
+    pub fn clear_junction_routing(&mut self, junction_index: usize) {
+        self.junction_routing.remove(&junction_index);
+    }
+
+    /// Changes what agents do when they reach a dead end. Defaults to [DeadEnd::Reverse].
+    pub fn set_dead_end(&mut self, dead_end: DeadEnd) {
+        self.dead_end = dead_end;
+    }
+
+    /// Seeds the random number generator used by [Routing::Random].
+    pub fn set_seed(&mut self, seed: u32) {
+        // xorshift gets stuck at zero
+        self.rng_state = seed.max(1);
+    }
+
+    /// Spawns a new agent at the [LED](Led) with index `led_index`, returning its id.
+    ///
+    /// Positive speeds travel towards the end of the LED's line segment, negative speeds towards its start.
+    /// Returns None if no LED exists at that index.
+    pub fn spawn<Color: ColorType>(
+        &mut self,
+        sled: &Sled<Color>,
+        led_index: usize,
+        speed: f32,
+        trail_length: f32,
+    ) -> Option<u32> {
+        let led = sled.get(led_index)?;
+        let segment = led.segment() as usize;
+        let t = led.position().distance(sled.line_segment(segment).start);
+
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.agents.push(Agent {
+            id,
+            segment,
+            t,
+            forward: speed >= 0.0,
+            speed: speed.abs(),
+            trail_length: trail_length.max(0.0),
+            history: smallvec![],
+        });
+
+        Some(id)
+    }
+
+    /// Removes the agent with the given id. Returns true if it existed.
+    pub fn despawn(&mut self, id: u32) -> bool {
+        let before = self.agents.len();
+        self.agents.retain(|agent| agent.id != id);
+        self.agents.len() != before
+    }
+
+    /// Removes every agent.
+    pub fn clear(&mut self) {
+        self.agents.clear();
+    }
+
+    /// Returns an Iterator over every active agent.
+    pub fn agents(&self) -> impl Iterator<Item = &Agent> {
+        self.agents.iter()
+    }
+
+    /// Returns a mutable reference to the agent with the given id, if it exists.
+    pub fn agent_mut(&mut self, id: u32) -> Option<&mut Agent> {
+        self.agents.iter_mut().find(|agent| agent.id == id)
+    }
+
+    /// Returns the number of active agents.
+    pub fn len(&self) -> usize {
+        self.agents.len()
+    }
+
+    /// Returns true if there are no active agents.
+    pub fn is_empty(&self) -> bool {
+        self.agents.is_empty()
+    }
+
+    /// Advances every agent by `delta` worth of travel, routing them through any junctions they reach.
+    pub fn step<Color: ColorType>(&mut self, sled: &Sled<Color>, delta: Duration) {
+        let dt = delta.as_secs_f32();
+        let mut agents = core::mem::take(&mut self.agents);
+        agents.retain_mut(|agent| {
+            let distance = agent.speed * dt;
+            self.advance(sled, agent, distance)
+        });
+        self.agents = agents;
+    }
+
+    /// Draws every agent onto the given Sled.
+    ///
+    /// `color_rule` is called for the [LED](Led) nearest each agent, as well as each LED covered by its trail.
+    /// It's passed the LED, the agent, and an alpha value representing how far along the trail that LED is. 0 = the agent itself, 1 = the end of the trail.
+    pub fn draw<Color: ColorType>(
+        &self,
+        sled: &mut Sled<Color>,
+        color_rule: impl Fn(&Led<Color>, &Agent, f32) -> Color,
+    ) {
+        for agent in &self.agents {
+            // (segment, forward, t at the leading edge of the covered part), starting at the head
+            let mut pieces: SmallVec<[(usize, bool, f32); 4]> =
+                smallvec![(agent.segment, agent.forward, agent.t)];
+            for (segment, forward) in agent.history.iter().rev() {
+                let length = sled.line_segment(*segment).length();
+                pieces.push((*segment, *forward, if *forward { length } else { 0.0 }));
+            }
+
+            let mut offset = 0.0;
+            for (segment_index, forward, t_end) in pieces {
+                let length = sled.line_segment(segment_index).length();
+                let range = sled.segment_led_range(segment_index);
+                let num_leds = range.len();
+                if num_leds > 0 {
+                    let half_spacing = length / num_leds as f32 * 0.5;
+                    if offset > agent.trail_length + half_spacing {
+                        break;
+                    }
+
+                    for (k, index) in range.enumerate() {
+                        let led_t = length * (k + 1) as f32 / num_leds as f32;
+                        let behind = if forward {
+                            t_end - led_t
+                        } else {
+                            led_t - t_end
+                        };
+                        let trail_dist = offset + behind;
+                        if behind > -half_spacing && trail_dist < agent.trail_length + half_spacing
+                        {
+                            let alpha = if agent.trail_length > 0.0 {
+                                (trail_dist / agent.trail_length).clamp(0.0, 1.0)
+                            } else {
+                                0.0
+                            };
+                            sled.update_led(index, |led| led.color = color_rule(led, agent, alpha));
+                        }
+                    }
+                }
+
+                offset += if forward { t_end } else { length - t_end };
+            }
+        }
+    }
+
+    /// Moves a single agent `distance` units along the strip. Returns false if the agent should be removed.
+    fn advance<Color: ColorType>(
+        &mut self,
+        sled: &Sled<Color>,
+        agent: &mut Agent,
+        mut distance: f32,
+    ) -> bool {
+        for _ in 0..MAX_HOPS_PER_STEP {
+            let length = sled.line_segment(agent.segment).length();
+            let room = if agent.forward {
+                length - agent.t
+            } else {
+                agent.t
+            };
+
+            if distance <= room {
+                agent.t += if agent.forward { distance } else { -distance };
+                return true;
+            }
+
+            distance -= room;
+            if !self.cross_junction(sled, agent) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Moves an agent sitting at the end of its segment onto the next one. Returns false if the agent should be removed.
+    fn cross_junction<Color: ColorType>(&mut self, sled: &Sled<Color>, agent: &mut Agent) -> bool {
+        let topology = sled.topology();
+        let (start_node, end_node) = topology.segment_nodes[agent.segment];
+        let node = if agent.forward { end_node } else { start_node };
+        let incoming = heading(sled, agent.segment, agent.forward);
+
+        // (segment, leaves forward, outgoing heading)
+        let candidates: SmallVec<[(usize, bool, Vec2); 4]> = topology.nodes[node]
+            .segments()
+            .iter()
+            .filter(|s| **s != agent.segment)
+            .map(|s| {
+                let forward = topology.segment_nodes[*s].0 == node;
+                (*s, forward, heading(sled, *s, forward))
+            })
+            .collect();
+
+        let (next_segment, next_forward) = if candidates.is_empty() {
+            match self.dead_end {
+                DeadEnd::Reverse => (agent.segment, !agent.forward),
+                DeadEnd::Remove => return false,
+            }
+        } else {
+            let routing = self
+                .junction_routing
+                .get(&node)
+                .copied()
+                .unwrap_or(self.routing);
+            let turn = |candidate: &(usize, bool, Vec2)| incoming.angle_to(candidate.2);
+
+            let choice = match routing {
+                Routing::Random => self.next_random() as usize % candidates.len(),
+                Routing::RoundRobin => {
+                    let counter = self.round_robin_counters.entry(node).or_insert(0);
+                    let choice = *counter % candidates.len();
+                    *counter = counter.wrapping_add(1);
+                    choice
+                }
+                Routing::Left => index_of_best(&candidates, |a, b| turn(a).total_cmp(&turn(b))),
+                Routing::Right => index_of_best(&candidates, |a, b| turn(b).total_cmp(&turn(a))),
+                Routing::Straight => {
+                    index_of_best(&candidates, |a, b| turn(b).abs().total_cmp(&turn(a).abs()))
+                }
+            };
+
+            (candidates[choice].0, candidates[choice].1)
+        };
+
+        agent.history.push((agent.segment, agent.forward));
+        agent.segment = next_segment;
+        agent.forward = next_forward;
+        agent.t = if next_forward {
+            0.0
+        } else {
+            sled.line_segment(next_segment).length()
+        };
+
+        // forget segments that lie entirely beyond the end of the trail
+        let mut covered = 0.0;
+        let mut keep = 0;
+        for (segment, _) in agent.history.iter().rev() {
+            if covered > agent.trail_length {
+                break;
+            }
+            covered += sled.line_segment(*segment).length();
+            keep += 1;
+        }
+        let forget = agent.history.len() - keep;
+        agent.history.drain(..forget);
+
+        true
+    }
+
+    fn next_random(&mut self) -> u32 {
+        // xorshift32
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng_state = x;
+        x
+    }
+}
+
+impl Default for Propagator {
+    fn default() -> Self {
+        Self::new(Routing::Random)
+    }
+}
+
+/// Returns the index of the greatest element according to `compare`.
+fn index_of_best<T>(items: &[T], compare: impl Fn(&T, &T) -> core::cmp::Ordering) -> usize {
+    items
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| compare(a, b))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
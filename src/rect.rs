@@ -0,0 +1,137 @@
+use core::ops::Range;
+
+use crate::Vec2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+/// An axis-aligned bounding rectangle, as returned by [Sled::domain()](crate::Sled::domain) and friends.
+///
+/// ```rust
+/// # use spatial_led::{Sled, Rect, Vec2};
+/// # use palette::rgb::Rgb;
+/// # let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+/// let bounds: Rect = sled.domain();
+/// println!("{} x {} around {}", bounds.width(), bounds.height(), bounds.center());
+///
+/// // pick a point anywhere in the room, with a bit of margin
+/// let spawn_area = bounds.expand(0.5);
+/// let point = spawn_area.lerp(Vec2::new(0.25, 0.75));
+/// assert!(spawn_area.contains(point));
+/// ```
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Rect {
+    /// Constructs a Rect from its minimum and maximum corners.
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Rect { min, max }
+    }
+
+    /// Constructs the smallest Rect containing every point in the iterator. Returns None if the iterator is empty.
+    pub fn from_points(points: impl IntoIterator<Item = Vec2>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Rect::new(first, first), |rect, p| {
+            Rect::new(rect.min.min(p), rect.max.max(p))
+        }))
+    }
+
+    /// Returns the extent of the Rect along the x axis.
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    /// Returns the extent of the Rect along the y axis.
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    /// Returns the width and height of the Rect as a Vec2.
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    /// Returns the point at the center of the Rect.
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Returns true if the given point lies inside the Rect or on its edge.
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    /// Returns true if the two Rects overlap.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    /// Returns a copy of the Rect grown by `margin` on every side. Negative margins shrink it.
+    pub fn expand(&self, margin: f32) -> Rect {
+        Rect::new(
+            self.min - Vec2::splat(margin),
+            self.max + Vec2::splat(margin),
+        )
+    }
+
+    /// Returns a copy of the Rect scaled by `factor` around its center.
+    pub fn scale(&self, factor: f32) -> Rect {
+        let center = self.center();
+        let half_size = self.size() * (0.5 * factor);
+        Rect::new(center - half_size, center + half_size)
+    }
+
+    /// Returns the smallest Rect containing both this and the other Rect.
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    /// Maps a point in normalized coordinates to world space, where `(0, 0)` is [Rect::min] and `(1, 1)` is [Rect::max].
+    pub fn lerp(&self, uv: Vec2) -> Vec2 {
+        self.min + self.size() * uv
+    }
+
+    /// Maps a point in world space to normalized coordinates. The inverse of [Rect::lerp()].
+    ///
+    /// Axes with zero extent map to 0.
+    pub fn uv_of(&self, point: Vec2) -> Vec2 {
+        let size = self.size();
+        let local = point - self.min;
+        Vec2::new(
+            if size.x > 0.0 { local.x / size.x } else { 0.0 },
+            if size.y > 0.0 { local.y / size.y } else { 0.0 },
+        )
+    }
+
+    /// Returns a random point inside the Rect.
+    ///
+    /// `random` should return uniformly distributed values between 0 and 1; it's called once per axis.
+    /// This lets you bring whichever random number generator you prefer.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Rect, Vec2};
+    /// use rand::Rng;
+    /// let mut rng = rand::thread_rng();
+    /// let rect = Rect::new(Vec2::new(-2.0, -1.0), Vec2::new(3.5, 2.0));
+    /// let point = rect.sample(|| rng.gen());
+    /// assert!(rect.contains(point));
+    /// ```
+    pub fn sample(&self, mut random: impl FnMut() -> f32) -> Vec2 {
+        let u = random();
+        let v = random();
+        self.lerp(Vec2::new(u, v))
+    }
+}
+
+impl From<Range<Vec2>> for Rect {
+    fn from(value: Range<Vec2>) -> Self {
+        Rect::new(value.start, value.end)
+    }
+}
+
+impl From<Rect> for Range<Vec2> {
+    fn from(value: Rect) -> Self {
+        value.min..value.max
+    }
+}
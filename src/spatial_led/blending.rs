@@ -0,0 +1,251 @@
+use alloc::format;
+
+#[cfg(not(feature = "std"))]
+use num_traits::float::Float as _;
+
+use crate::{
+    color::{Blend, BlendMode, ColorType},
+    error::SledError,
+    led::Led,
+    spatial_led::{Filter, Mask, Sled},
+    Vec2,
+};
+
+/// # Blended write methods
+///
+/// Counterparts to the usual set and map methods that [blend](Blend) new colors on top of the existing ones instead of replacing them, so several effects can be layered in one frame.
+impl<Color: ColorType + Blend> Sled<Color> {
+    /// Blends `color` on top of the [LED](Led) at `index` using the given [BlendMode].
+    /// Returns an [error](SledError) if no LED exists at that index.
+    ///
+    /// O(1)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, SledError, color::BlendMode};
+    ///# fn demo() -> Result<(), SledError> {
+    ///# let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap")?;
+    /// sled.set(0, (0.5, 0.0, 0.0))?;
+    /// sled.blend(0, (0.0, 0.5, 0.0), BlendMode::Add)?;
+    /// assert_eq!(sled.get(0).unwrap().color, (0.5, 0.5, 0.0));
+    ///# Ok(())
+    ///# }
+    /// ```
+    pub fn blend(&mut self, index: usize, color: Color, mode: BlendMode) -> Result<(), SledError> {
+        if index >= self.layout.num_leds {
+            return SledError::new(format!("LED at index {} does not exist.", index)).as_err();
+        }
+
+        self.colors[index] = self.colors[index].blend(color, mode);
+        Ok(())
+    }
+
+    /// Blends `color` on top of every [LED](Led) in the system using the given [BlendMode].
+    ///
+    /// O(LEDS)
+    pub fn blend_all(&mut self, color: Color, mode: BlendMode) {
+        self.update_all(|led| {
+            led.color = led.color.blend(color, mode);
+        });
+    }
+
+    /// Blends `color` on top of each [LED](Led) in the given [Filter] using the given [BlendMode].
+    ///
+    /// O(LEDS_IN_FILTER)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, color::BlendMode};
+    ///# let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// let left = sled.filter_by_pos(|pos| pos.x < 0.0);
+    /// let top = sled.filter_by_pos(|pos| pos.y > 1.0);
+    /// // where the two regions overlap, the colors add up to white
+    /// sled.blend_filter(&left, (1.0, 1.0, 0.0), BlendMode::Add);
+    /// sled.blend_filter(&top, (0.0, 0.0, 1.0), BlendMode::Add);
+    /// ```
+    pub fn blend_filter(&mut self, filter: &Filter, color: Color, mode: BlendMode) {
+        for i in filter {
+            let led_color = &mut self.colors[i as usize];
+            *led_color = led_color.blend(color, mode);
+        }
+    }
+
+    /// Blends `color` on top of each [LED](Led) in the line segment with index `segment_index` using the given [BlendMode].
+    /// Returns an [error](SledError) if no line segment with the given index exists.
+    ///
+    /// O(LEDS_IN_SEGMENT)
+    pub fn blend_segment(
+        &mut self,
+        segment_index: usize,
+        color: Color,
+        mode: BlendMode,
+    ) -> Result<(), SledError> {
+        if segment_index >= self.layout.line_segment_endpoint_indices.len() {
+            return SledError::new(format!(
+                "No line segment of index {} exists.",
+                segment_index
+            ))
+            .as_err();
+        }
+
+        let (start, end) = self.layout.line_segment_endpoint_indices[segment_index];
+        self.update_leds(start..end, |led| {
+            led.color = led.color.blend(color, mode);
+        });
+
+        Ok(())
+    }
+
+    /// Blends `color` on top of each [LED](Led) within `dist` of the center point using the given [BlendMode].
+    ///
+    /// Returns true if any LEDs were blended, false otherwise.
+    ///
+    /// O(LEDS)
+    pub fn blend_within_dist(&mut self, dist: f32, color: Color, mode: BlendMode) -> bool {
+        self.blend_within_dist_from(dist, self.layout.center_point, color, mode)
+    }
+
+    /// Blends `color` on top of each [LED](Led) within `dist` of `pos` using the given [BlendMode].
+    ///
+    /// Returns true if any LEDs were blended, false otherwise.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Vec2, color::BlendMode};
+    ///# let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// // two overlapping spotlights; the overlap is brighter rather than overwritten
+    /// sled.blend_within_dist_from(2.0, Vec2::new(-1.0, 0.5), (0.6, 0.0, 0.3), BlendMode::Screen);
+    /// sled.blend_within_dist_from(2.0, Vec2::new(1.0, 0.5), (0.0, 0.6, 0.3), BlendMode::Screen);
+    /// ```
+    pub fn blend_within_dist_from(
+        &mut self,
+        dist: f32,
+        pos: Vec2,
+        color: Color,
+        mode: BlendMode,
+    ) -> bool {
+        let target_sq = dist.powi(2);
+        let mut changes_made = false;
+
+        self.update_all(|led| {
+            if led.position().distance_squared(pos) < target_sq {
+                led.color = led.color.blend(color, mode);
+                changes_made = true;
+            }
+        });
+
+        changes_made
+    }
+
+    /// Maps LEDs to a color, blending the result on top of their existing color using the given [BlendMode].
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, color::BlendMode};
+    ///# let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// sled.set_all((0.2, 0.2, 0.2));
+    /// // darken everything toward the edges of the room
+    /// sled.blend_map(BlendMode::Multiply, |led| {
+    ///     let v = (1.0 - led.distance() / 5.0).max(0.0);
+    ///     (v, v, v)
+    /// });
+    /// ```
+    pub fn blend_map(&mut self, mode: BlendMode, led_to_color_map: impl Fn(&Led<Color>) -> Color) {
+        self.update_all(|led| {
+            led.color = led.color.blend(led_to_color_map(led), mode);
+        });
+    }
+
+    /// Maps each [LED](Led) in the given [Filter] to a color, blending the result on top of its existing color using the given [BlendMode].
+    ///
+    /// O(LEDS_IN_FILTER)
+    pub fn blend_map_filter(
+        &mut self,
+        filter: &Filter,
+        mode: BlendMode,
+        led_to_color_map: impl Fn(&Led<Color>) -> Color,
+    ) {
+        for i in filter {
+            self.update_led(i as usize, |led| {
+                led.color = led.color.blend(led_to_color_map(led), mode)
+            });
+        }
+    }
+
+    /// Maps LED positions to a color, blending the result on top of their existing color using the given [BlendMode].
+    ///
+    /// O(LEDS)
+    pub fn blend_map_by_pos(&mut self, mode: BlendMode, pos_to_color_map: impl Fn(Vec2) -> Color) {
+        self.blend_map(mode, |led| pos_to_color_map(led.position()));
+    }
+
+    /// Maps LED distances from the center point to a color, blending the result on top of their existing color using the given [BlendMode].
+    ///
+    /// O(LEDS)
+    pub fn blend_map_by_dist(&mut self, mode: BlendMode, dist_to_color_map: impl Fn(f32) -> Color) {
+        self.blend_map(mode, |led| dist_to_color_map(led.distance()));
+    }
+
+    /// Maps LED distances from `pos` to a color, blending the result on top of their existing color using the given [BlendMode].
+    ///
+    /// O(LEDS)
+    pub fn blend_map_by_dist_from(
+        &mut self,
+        pos: Vec2,
+        mode: BlendMode,
+        dist_to_color_map: impl Fn(f32) -> Color,
+    ) {
+        self.blend_map(mode, |led| {
+            dist_to_color_map(led.position().distance(pos))
+        });
+    }
+
+    /// Maps LED angles around the center point to a color, blending the result on top of their existing color using the given [BlendMode].
+    ///
+    /// O(LEDS)
+    pub fn blend_map_by_angle(
+        &mut self,
+        mode: BlendMode,
+        angle_to_color_map: impl Fn(f32) -> Color,
+    ) {
+        self.blend_map(mode, |led| angle_to_color_map(led.angle()));
+    }
+
+    /// Maps LED [path positions](Led::path_pos) to a color, blending the result on top of their existing color using the given [BlendMode].
+    ///
+    /// O(LEDS)
+    pub fn blend_map_by_path_pos(
+        &mut self,
+        mode: BlendMode,
+        path_pos_to_color_map: impl Fn(f32) -> Color,
+    ) {
+        self.blend_map(mode, |led| path_pos_to_color_map(led.path_pos()));
+    }
+
+    /// Maps each [LED](Led) with a weight above zero in the given [Mask] to a color, blending the result on top of its existing color using the given [BlendMode].
+    /// Each LED's weight is used as the opacity of the blend.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Vec2, color::BlendMode};
+    ///# let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// let glow = sled.mask_by_dist_from(Vec2::new(0.0, 1.0), 0.5, 2.0);
+    /// sled.blend_mask(&glow, BlendMode::Add, |_| (1.0, 0.5, 0.0));
+    /// ```
+    pub fn blend_mask(
+        &mut self,
+        mask: &Mask,
+        mode: BlendMode,
+        led_to_color_map: impl Fn(&Led<Color>) -> Color,
+    ) {
+        self.update_all(|led| {
+            let weight = mask.get(led.index() as usize);
+            if weight > 0.0 {
+                led.color = led
+                    .color
+                    .blend_with_opacity(led_to_color_map(led), mode, weight);
+            }
+        });
+    }
+}
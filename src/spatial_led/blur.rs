@@ -0,0 +1,267 @@
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::float::Float as _;
+
+use crate::{
+    color::{Blend, ColorType},
+    error::SledError,
+    spatial_led::Sled,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Which [LEDs](crate::Led) count as neighbors when building a [BlurKernel].
+pub enum Neighborhood {
+    /// Each LED and the LEDs [directly connected](Sled::neighbor_indices) to it. The cheapest option, and the classic choice for diffusion.
+    Adjacent,
+    /// Every LED within `radius` of each other, measured along the strips. Light spreads around corners and through [junctions](crate::Junction), but never jumps between strips that merely pass close by.
+    Path { radius: f32 },
+    /// Every LED within `radius` of each other in a straight line, whether or not they're connected.
+    Euclidean { radius: f32 },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+/// How a [BlurKernel] weighs each neighbor by its distance.
+pub enum KernelShape {
+    /// Every neighbor counts equally.
+    #[default]
+    Box,
+    /// Neighbors are weighed by a normal distribution with standard deviation `sigma`, so closer LEDs count for more.
+    Gaussian { sigma: f32 },
+}
+
+impl KernelShape {
+    fn weight(self, dist: f32) -> f32 {
+        match self {
+            KernelShape::Box => 1.0,
+            KernelShape::Gaussian { sigma } => {
+                let sigma = sigma.max(f32::EPSILON);
+                (-(dist * dist) / (2.0 * sigma * sigma)).exp()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Precomputed neighbor weights for blurring and diffusing colors across a [Sled].
+///
+/// Finding each LED's neighbors is relatively expensive, so like a [PathField](crate::PathField), a BlurKernel is best built once and saved to [Data](crate::driver::Data).
+/// Applying it is then O(LEDS * NEIGHBORS), cheap enough to run every frame.
+///
+/// Kernels work with any color type that implements [Blend], since each LED's new color is a weighted [mix](Blend::mix) of its neighbors.
+///
+/// ```rust
+/// # use spatial_led::{Sled, BlurKernel, KernelShape, Neighborhood};
+/// # let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+/// // a soft glow that bleeds around corners
+/// let kernel = sled.blur_kernel(
+///     Neighborhood::Path { radius: 0.5 },
+///     KernelShape::Gaussian { sigma: 0.2 },
+/// );
+///
+/// sled.set_all((0.0, 0.0, 0.0));
+/// sled.set(100, (1.0, 1.0, 1.0)).unwrap();
+/// sled.blur(&kernel).unwrap();
+///
+/// assert!(sled.get(100).unwrap().color.0 < 1.0);
+/// assert!(sled.get(101).unwrap().color.0 > 0.0);
+/// ```
+pub struct BlurKernel {
+    /// `offsets[i]..offsets[i + 1]` is the range of `entries` belonging to LED `i`.
+    offsets: Vec<usize>,
+    /// Neighbor index and normalized weight.
+    entries: Vec<(u16, f32)>,
+}
+
+impl BlurKernel {
+    /// Returns the number of LEDs this kernel was built for.
+    pub fn num_leds(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Returns an Iterator over the index and weight of each neighbor of the [LED](crate::Led) at `index`, including the LED itself. Weights sum to 1.
+    ///
+    /// If no LED exists at `index`, the Iterator will be empty.
+    pub fn weights(&self, index: usize) -> impl Iterator<Item = (usize, f32)> + '_ {
+        let range = match (self.offsets.get(index), self.offsets.get(index + 1)) {
+            (Some(start), Some(end)) => *start..*end,
+            _ => 0..0,
+        };
+
+        self.entries[range].iter().map(|(i, w)| (*i as usize, *w))
+    }
+
+    /// Returns the weighted mix of the neighbors of the LED at `index`, reading colors from `colors`.
+    fn sample<Color: ColorType + Blend>(&self, colors: &[Color], index: usize) -> Color {
+        let mut entries = self.weights(index);
+        let Some((first, mut total)) = entries.next() else {
+            return colors[index];
+        };
+
+        // mixing each neighbor in by its share of the running total yields the weighted average
+        let mut sum = colors[first];
+        for (neighbor, weight) in entries {
+            total += weight;
+            if total > 0.0 {
+                sum = sum.mix(colors[neighbor], weight / total);
+            }
+        }
+
+        sum
+    }
+
+    fn check_len<Color: ColorType>(&self, sled: &Sled<Color>) -> Result<(), SledError> {
+        if self.num_leds() != sled.num_leds() {
+            return SledError::new(format!(
+                "BlurKernel was built for {} LEDs, but the Sled has {}.",
+                self.num_leds(),
+                sled.num_leds()
+            ))
+            .as_err();
+        }
+
+        Ok(())
+    }
+}
+
+/// # Blur and diffusion methods.
+impl<Color: ColorType> Sled<Color> {
+    /// Precomputes a [BlurKernel] for this Sled's layout, finding the neighbors of each [LED](crate::Led) and weighing them by `shape`.
+    ///
+    /// O(LEDS * NEIGHBORS * log(NEIGHBORS)) for [Neighborhood::Adjacent] and [Neighborhood::Path], O(LEDS^2) for [Neighborhood::Euclidean].
+    pub fn blur_kernel(&self, neighborhood: Neighborhood, shape: KernelShape) -> BlurKernel {
+        let num_leds = self.layout.num_leds;
+        let mut offsets = Vec::with_capacity(num_leds + 1);
+        let mut entries = Vec::new();
+        let mut found = Vec::new();
+        let mut distances = match neighborhood {
+            Neighborhood::Path { .. } => vec![f32::INFINITY; num_leds],
+            _ => vec![],
+        };
+
+        offsets.push(0);
+        for index in 0..num_leds {
+            let pos = self.layout.leds[index].position();
+            found.clear();
+
+            match neighborhood {
+                Neighborhood::Adjacent => {
+                    found.push((index, 0.0));
+                    found.extend(self.neighbor_indices(index).map(|neighbor| {
+                        (
+                            neighbor,
+                            pos.distance(self.layout.leds[neighbor].position()),
+                        )
+                    }));
+                }
+                Neighborhood::Path { radius } => {
+                    self.path_neighborhood(index, radius, &mut distances, &mut found);
+                }
+                Neighborhood::Euclidean { radius } => {
+                    found.extend(self.layout.leds.iter().enumerate().filter_map(|(i, led)| {
+                        let dist = pos.distance(led.position());
+                        (dist <= radius).then_some((i, dist))
+                    }));
+                }
+            }
+
+            let total: f32 = found.iter().map(|(_, dist)| shape.weight(*dist)).sum();
+            entries.extend(found.iter().map(|(i, dist)| {
+                let weight = if total > 0.0 {
+                    shape.weight(*dist) / total
+                } else {
+                    0.0
+                };
+                (*i as u16, weight)
+            }));
+            offsets.push(entries.len());
+        }
+
+        BlurKernel { offsets, entries }
+    }
+}
+
+impl<Color: ColorType + Blend> Sled<Color> {
+    /// Replaces the color of each [LED](crate::Led) with the weighted mix of its neighbors in `kernel`.
+    ///
+    /// This copies the Sled's colors once per call. To blur without allocating, keep a second Sled sharing the same [Layout](crate::Layout) and use [Sled::blur_into()].
+    ///
+    /// Returns an [error](SledError) if `kernel` was built for a different number of LEDs.
+    ///
+    /// O(LEDS * NEIGHBORS)
+    pub fn blur(&mut self, kernel: &BlurKernel) -> Result<(), SledError> {
+        kernel.check_len(self)?;
+        let source = self.colors.clone();
+        for (index, color) in self.colors.iter_mut().enumerate() {
+            *color = kernel.sample(&source, index);
+        }
+
+        Ok(())
+    }
+
+    /// Writes a blurred copy of this Sled's colors into `target`, leaving this Sled untouched. See [Sled::blur()].
+    ///
+    /// Returns an [error](SledError) if `kernel` or `target` has a different number of LEDs than this Sled.
+    ///
+    /// O(LEDS * NEIGHBORS)
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, KernelShape, Neighborhood};
+    /// # let mut front = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// let mut back = front.clone();
+    /// let kernel = front.blur_kernel(Neighborhood::Adjacent, KernelShape::Box);
+    ///
+    /// front.set(50, (1.0, 1.0, 1.0)).unwrap();
+    /// front.blur_into(&kernel, &mut back).unwrap();
+    /// assert_eq!(front.get(50).unwrap().color, (1.0, 1.0, 1.0));
+    /// assert!(back.get(50).unwrap().color.0 < 1.0);
+    /// ```
+    pub fn blur_into(
+        &self,
+        kernel: &BlurKernel,
+        target: &mut Sled<Color>,
+    ) -> Result<(), SledError> {
+        kernel.check_len(self)?;
+        kernel.check_len(target)?;
+        for (index, color) in target.colors.iter_mut().enumerate() {
+            *color = kernel.sample(&self.colors, index);
+        }
+
+        Ok(())
+    }
+
+    /// Runs one step of diffusion: moves the color of each [LED](crate::Led) a fraction `rate` of the way toward the weighted mix of its neighbors in `kernel`.
+    ///
+    /// Called every frame, light and heat spread gradually through the layout. A `rate` of 1 is equivalent to [Sled::blur()].
+    ///
+    /// Returns an [error](SledError) if `kernel` was built for a different number of LEDs.
+    ///
+    /// O(LEDS * NEIGHBORS)
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, SledError, KernelShape, Neighborhood, driver::Driver};
+    /// let mut driver = Driver::<(f32, f32, f32)>::new();
+    /// driver.set_startup_commands(|sled, data| {
+    ///     data.set("kernel", sled.blur_kernel(Neighborhood::Adjacent, KernelShape::Box));
+    ///     Ok(())
+    /// });
+    /// driver.set_draw_commands(|sled, data, time| {
+    ///     // a heat source at one end of the strip, spreading outward
+    ///     sled.set(0, (1.0, 0.4, 0.0))?;
+    ///     sled.diffuse(data.get("kernel")?, 10.0 * time.delta.as_secs_f32())?;
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn diffuse(&mut self, kernel: &BlurKernel, rate: f32) -> Result<(), SledError> {
+        kernel.check_len(self)?;
+        let rate = rate.clamp(0.0, 1.0);
+        let source = self.colors.clone();
+        for (index, color) in self.colors.iter_mut().enumerate() {
+            *color = color.mix(kernel.sample(&source, index), rate);
+        }
+
+        Ok(())
+    }
+}
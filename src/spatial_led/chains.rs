@@ -0,0 +1,169 @@
+use core::ops::Range;
+
+use alloc::format;
+
+use crate::{
+    color::ColorType,
+    error::SledError,
+    led::Led,
+    rect::Rect,
+    spatial_led::{Filter, Sled},
+};
+
+/// # Chain and path-based read and write methods.
+impl<Color: ColorType> Sled<Color> {
+    /// Returns the set of all [LEDs](Led) belonging to the chain with index `chain_index`.
+    ///
+    /// A chain is a run of line segments connected by `-->` in the [config file](Sled::new).
+    ///
+    /// O(LEDS_IN_CHAIN)
+    pub fn chain(&self, chain_index: usize) -> Option<Filter> {
+        let (start, end) = *self.layout.chain_endpoint_indices.get(chain_index)?;
+        Some(Filter::from_range(start..end))
+    }
+
+    /// Returns a bounding box around the [LEDs](Led) belonging to the chain with index `chain_index`.
+    ///
+    /// Returns None if the chain doesn't exist or holds no LEDs.
+    ///
+    /// O(LEDS_IN_CHAIN)
+    pub fn chain_domain(&self, chain_index: usize) -> Option<Rect> {
+        let (start, end) = *self.layout.chain_endpoint_indices.get(chain_index)?;
+        Rect::from_points(
+            self.layout.leds[start..end]
+                .iter()
+                .map(|led| led.position()),
+        )
+    }
+
+    /// Modulates the color of each [LED](Led) belonging to the chain with index `chain_index` given a color rule function.
+    /// Returns an [error](SledError) if there is no chain with the given index.
+    ///
+    /// O(LEDS_IN_CHAIN)
+    ///
+    ///```rust
+    ///# use spatial_led::{Sled, SledError};
+    ///# use palette::rgb::Rgb;
+    ///# fn demo() -> Result<(), SledError> {
+    ///# let mut sled = Sled::<Rgb>::new("./benches/config.yap")?;
+    /// sled.modulate_chain(0, |led| led.color * 0.5)?;
+    ///# Ok(())
+    ///# }
+    /// ```
+    pub fn modulate_chain<F: Fn(&Led<Color>) -> Color>(
+        &mut self,
+        chain_index: usize,
+        color_rule: F,
+    ) -> Result<(), SledError> {
+        if chain_index >= self.layout.chain_endpoint_indices.len() {
+            return SledError::new(format!("Chain of index {} does not exist.", chain_index))
+                .as_err();
+        }
+
+        let (start, end) = self.layout.chain_endpoint_indices[chain_index];
+        self.update_leds(start..end, |led| {
+            led.color = color_rule(led);
+        });
+
+        Ok(())
+    }
+
+    /// Sets the color of each [LED](Led) belonging to the chain with index `chain_index`.
+    /// Returns an [error](SledError) if there is no chain with the given index.
+    ///
+    /// O(LEDS_IN_CHAIN)
+    pub fn set_chain(&mut self, chain_index: usize, color: Color) -> Result<(), SledError> {
+        if chain_index >= self.layout.chain_endpoint_indices.len() {
+            return SledError::new(format!("No chain of index {} exists.", chain_index)).as_err();
+        }
+
+        let (start, end) = self.layout.chain_endpoint_indices[chain_index];
+        self.update_leds(start..end, |led| {
+            led.color = color;
+        });
+
+        Ok(())
+    }
+
+    /// For-each method granting mutable access to each [LED](Led) belonging to the chain with index `chain_index`.
+    /// Also passes an "alpha" value into the closure, representing how far along the whole chain you are by arc length. 0 = start of the chain, 1 = end.
+    ///
+    /// Unlike [Sled::for_each_in_segment()], alpha does not reset at each vertex. If you need the distance in world units, use [Led::chain_pos()].
+    ///
+    /// Returns an [error](SledError) if no chain of given index exists.
+    ///
+    /// O(LEDS_IN_CHAIN)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled};
+    /// use palette::rgb::Rgb;
+    ///# let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// sled.for_each_in_chain(0, |led, alpha| {
+    ///     led.color = Rgb::new(alpha, 0.0, 1.0 - alpha);
+    /// });
+    /// ```
+    pub fn for_each_in_chain<F: FnMut(&mut Led<Color>, f32)>(
+        &mut self,
+        chain_index: usize,
+        mut func: F,
+    ) -> Result<(), SledError> {
+        if chain_index >= self.layout.chain_endpoint_indices.len() {
+            return Err(SledError {
+                message: format!("No chain of index {} exists.", chain_index),
+            });
+        }
+
+        let (start, end) = self.layout.chain_endpoint_indices[chain_index];
+        let length = self.layout.chain_lengths[chain_index];
+        // zero-length chains have nowhere to progress along, so every LED sits at alpha 0
+        let inv_length = if length > 0.0 { length.recip() } else { 0.0 };
+
+        self.update_leds(start..end, |led| {
+            let alpha = led.chain_pos() * inv_length;
+            func(led, alpha);
+        });
+
+        Ok(())
+    }
+
+    /// Returns the set of all [LEDs](Led) in the chain with index `chain_index` whose [chain position](Led::chain_pos) lies within `interval`.
+    ///
+    /// Returns None if no chain of given index exists.
+    ///
+    /// O(log(LEDS_IN_CHAIN) + LEDS_IN_INTERVAL)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Filter};
+    ///# use palette::rgb::Rgb;
+    ///# let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// // the first two units of the first chain
+    /// let head: Filter = sled.chain_interval(0, 0.0..2.0).unwrap();
+    /// sled.set_filter(&head, Rgb::new(1.0, 1.0, 1.0));
+    /// ```
+    pub fn chain_interval(&self, chain_index: usize, interval: Range<f32>) -> Option<Filter> {
+        let (start, end) = *self.layout.chain_endpoint_indices.get(chain_index)?;
+        let chain = &self.layout.leds[start..end];
+        let first = chain.partition_point(|led| led.chain_pos() < interval.start);
+        let last = chain.partition_point(|led| led.chain_pos() < interval.end);
+        Some(Filter::from_range(start + first..start + last.max(first)))
+    }
+
+    /// Returns the set of all [LEDs](Led) whose [path position](Led::path_pos) lies within `interval`.
+    ///
+    /// O(log(LEDS) + LEDS_IN_INTERVAL)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Filter};
+    ///# use palette::rgb::Rgb;
+    ///# let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let half = sled.path_length() * 0.5;
+    /// let first_half: Filter = sled.path_interval(0.0..half);
+    /// sled.set_filter(&first_half, Rgb::new(0.0, 0.0, 1.0));
+    /// ```
+    pub fn path_interval(&self, interval: Range<f32>) -> Filter {
+        let leds = &self.layout.leds;
+        let first = leds.partition_point(|led| led.path_pos() < interval.start);
+        let last = leds.partition_point(|led| led.path_pos() < interval.end);
+        Filter::from_range(first..last.max(first))
+    }
+}
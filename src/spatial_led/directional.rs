@@ -1,10 +1,34 @@
-use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
 
 use crate::Vec2;
-use crate::{color::ColorType, led::Led, Filter, Sled};
+use crate::{color::ColorType, config::LineSegment, led::Led, Filter, Sled};
 
 use smallvec::SmallVec;
 
+#[cfg(not(feature = "std"))]
+use num_traits::float::Float as _;
+
+/// Intersections closer than this to a ray's origin are ignored, so that reflected rays don't immediately re-hit their mirror.
+const RAY_EPSILON: f32 = 1e-4;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// A point where a ray cast by [Sled::raycast()] or [Sled::raycast_bounces()] struck something.
+pub struct RayHit {
+    /// Where the hit occurred, in world space.
+    pub position: Vec2,
+    /// The index of the [LED](Led) closest to the hit, if the ray struck one of the Sled's line segments.
+    pub led_index: Option<usize>,
+    /// The total distance the ray travelled to get here, including any previous bounces.
+    pub distance: f32,
+    /// The angle between the incoming ray and the surface normal, in radians. 0 means a head-on hit, pi/2 a grazing one.
+    pub incidence_angle: f32,
+    /// The number of reflections the ray had already made before this hit.
+    pub bounces: usize,
+    /// True if the ray reflected off the surface here, false if it passed through or stopped.
+    pub reflected: bool,
+}
+
 /// # directional read and write methods
 impl<Color: ColorType> Sled<Color> {
     fn raycast_for_indices(&self, start: Vec2, dir: Vec2) -> SmallVec<[usize; 4]> {
@@ -12,7 +36,7 @@ impl<Color: ColorType> Sled<Color> {
         let end = start + dir * dist;
 
         let mut intersections = smallvec::smallvec![];
-        for (seg_index, segment) in self.line_segments.iter().enumerate() {
+        for (seg_index, segment) in self.layout.line_segments.iter().enumerate() {
             if let Some(t) = segment.intersects_line(start, end) {
                 let index = self.alpha_to_index(t, seg_index);
                 intersections.push(index);
@@ -22,6 +46,183 @@ impl<Color: ColorType> Sled<Color> {
         intersections
     }
 
+    /// Traces a ray across the given mirrors, recording every LED segment it crosses along the way.
+    /// If `mirrors` is None, the Sled's own line segments act as the mirrors.
+    fn trace_ray(
+        &self,
+        mut origin: Vec2,
+        dir: Vec2,
+        max_bounces: usize,
+        mirrors: Option<&[LineSegment]>,
+    ) -> Vec<RayHit> {
+        let mut hits = vec![];
+        let mut dir = dir.normalize_or_zero();
+        if dir == Vec2::ZERO {
+            return hits;
+        }
+
+        let mirrors = mirrors.unwrap_or(&self.layout.line_segments);
+        let mut travelled = 0.0;
+        let mut bounces = 0;
+
+        loop {
+            let closest_mirror = mirrors
+                .iter()
+                .filter_map(|m| {
+                    let (_, dist) = m.intersects_ray(origin, dir)?;
+                    (dist > RAY_EPSILON).then_some((m, dist))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let mirror_dist = closest_mirror.map_or(f32::INFINITY, |(_, d)| d);
+
+            // every LED segment crossed on the way to the mirror
+            let mut led_hits: SmallVec<[(usize, f32, f32); 4]> = self
+                .layout
+                .line_segments
+                .iter()
+                .enumerate()
+                .filter_map(|(seg_index, segment)| {
+                    let (t, dist) = segment.intersects_ray(origin, dir)?;
+                    (dist > RAY_EPSILON && dist <= mirror_dist + RAY_EPSILON)
+                        .then_some((seg_index, t, dist))
+                })
+                .collect();
+            led_hits.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+            let will_reflect = closest_mirror.is_some() && bounces < max_bounces;
+            let mut mirror_reported = false;
+
+            for (seg_index, t, dist) in led_hits {
+                let on_mirror =
+                    closest_mirror.is_some() && (dist - mirror_dist).abs() <= RAY_EPSILON;
+                mirror_reported |= on_mirror;
+                hits.push(RayHit {
+                    position: origin + dir * dist,
+                    led_index: Some(self.alpha_to_nearest_index(t, seg_index)),
+                    distance: travelled + dist,
+                    incidence_angle: incidence_angle(&self.layout.line_segments[seg_index], dir),
+                    bounces,
+                    reflected: on_mirror && will_reflect,
+                });
+            }
+
+            let Some((mirror, dist)) = closest_mirror else {
+                break;
+            };
+
+            if !mirror_reported {
+                hits.push(RayHit {
+                    position: origin + dir * dist,
+                    led_index: None,
+                    distance: travelled + dist,
+                    incidence_angle: incidence_angle(mirror, dir),
+                    bounces,
+                    reflected: will_reflect,
+                });
+            }
+
+            if !will_reflect {
+                break;
+            }
+
+            let normal = (mirror.end - mirror.start).perp().normalize_or_zero();
+            origin += dir * dist;
+            travelled += dist;
+            dir = dir - 2.0 * dir.dot(normal) * normal;
+            bounces += 1;
+        }
+
+        hits
+    }
+
+    /// Casts a ray from `start` in direction `dir`, returning the first point where it strikes one of the Sled's line segments.
+    ///
+    /// Returns None if the ray doesn't hit anything.
+    ///
+    /// O(SEGMENTS)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Vec2};
+    ///# use palette::rgb::Rgb;
+    ///# let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let hit = sled.raycast(sled.center_point(), Vec2::new(0.0, 1.0)).unwrap();
+    /// assert_eq!(hit.position.y, 2.0);
+    /// ```
+    ///
+    /// A hit reports the LED nearest to it on the segment that was struck, even at the very end of that segment.
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Vec2};
+    /// let sled = Sled::<f32>::new_from_str(
+    ///     "center: (0, 0)\ndensity: 10\n--segments--\n(0, 0) --> (1, 0)",
+    /// ).unwrap();
+    /// let down = Vec2::new(0.0, -1.0);
+    ///
+    /// let hit = sled.raycast(Vec2::new(0.3, 1.0), down).unwrap();
+    /// assert_eq!(hit.led_index, Some(2));
+    ///
+    /// let hit = sled.raycast(Vec2::new(1.0, 1.0), down).unwrap();
+    /// assert_eq!(hit.led_index, Some(9));
+    /// ```
+    pub fn raycast(&self, start: Vec2, dir: Vec2) -> Option<RayHit> {
+        self.trace_ray(start, dir, 0, None).into_iter().next()
+    }
+
+    /// Casts a ray from `start` in direction `dir` that reflects off the Sled's line segments up to `max_bounces` times, like a laser bouncing around a room of mirrors.
+    ///
+    /// Returns every hit in the order the ray reached them.
+    ///
+    /// O(SEGMENTS * BOUNCES)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Vec2};
+    ///# use palette::rgb::Rgb;
+    ///# let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let hits = sled.raycast_bounces(sled.center_point(), Vec2::new(1.0, 0.3), 8);
+    /// for hit in hits {
+    ///     if let Some(index) = hit.led_index {
+    ///         let brightness = 1.0 / (1.0 + hit.distance);
+    ///         sled.set(index, Rgb::new(brightness, 0.0, 0.0)).unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn raycast_bounces(&self, start: Vec2, dir: Vec2, max_bounces: usize) -> Vec<RayHit> {
+        self.trace_ray(start, dir, max_bounces, None)
+    }
+
+    /// Like [Sled::raycast_bounces()], but the ray reflects off the given `mirrors` (pairs of endpoints) instead of the Sled's line segments.
+    ///
+    /// LED segments that aren't mirrors are still reported as hits, but the ray passes straight through them.
+    /// To make some of the Sled's own segments reflective, include them in `mirrors`; see [Sled::segment_endpoints()].
+    ///
+    /// O((SEGMENTS + MIRRORS) * BOUNCES)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Vec2};
+    ///# use palette::rgb::Rgb;
+    ///# let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// // a single diagonal mirror in the middle of the room
+    /// let mirrors = [(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 1.5))];
+    /// let hits = sled.raycast_bounces_off(Vec2::new(-1.5, 1.5), Vec2::new(1.0, 0.0), &mirrors, 4);
+    /// assert!(hits[0].reflected);
+    /// ```
+    pub fn raycast_bounces_off(
+        &self,
+        start: Vec2,
+        dir: Vec2,
+        mirrors: &[(Vec2, Vec2)],
+        max_bounces: usize,
+    ) -> Vec<RayHit> {
+        let mirrors: Vec<LineSegment> = mirrors
+            .iter()
+            .map(|(start, end)| LineSegment {
+                start: *start,
+                end: *end,
+            })
+            .collect();
+        self.trace_ray(start, dir, max_bounces, Some(&mirrors))
+    }
+
     /* direction setters/getters */
 
     /// Returns A [Filter] containing each [LED](Led) in the given direction from the center point.
@@ -31,7 +232,7 @@ impl<Color: ColorType> Sled<Color> {
     ///
     /// O(SEGMENTS)
     pub fn at_dir(&self, dir: Vec2) -> Filter {
-        self.at_dir_from(dir, self.center_point)
+        self.at_dir_from(dir, self.layout.center_point)
     }
 
     /// Returns A [Filter] containing each [LED](Led) in the given direction from a given point.
@@ -39,12 +240,9 @@ impl<Color: ColorType> Sled<Color> {
     ///
     /// Currently returns no more than 4 LEDs, may change in the future.
     pub fn at_dir_from(&self, dir: Vec2, pos: Vec2) -> Filter {
-        let intersecting_indices = self.raycast_for_indices(pos, dir);
-        intersecting_indices
-            .iter()
-            .map(|i| *i as u16)
-            .collect::<BTreeSet<u16>>()
-            .into()
+        let mut intersecting = Filter::with_capacity(self.num_leds());
+        intersecting.extend(self.raycast_for_indices(pos, dir).iter().map(|i| *i as u16));
+        intersecting
     }
 
     /// Modulates the color of each [LED](Led) in the given direction from the center point.
@@ -68,7 +266,7 @@ impl<Color: ColorType> Sled<Color> {
         dir: Vec2,
         color_rule: F,
     ) -> bool {
-        self.modulate_at_dir_from(dir, self.center_point, color_rule)
+        self.modulate_at_dir_from(dir, self.layout.center_point, color_rule)
     }
 
     /// Modulates the color of each [LED](Led) in the given direction from a given point.
@@ -104,8 +302,7 @@ impl<Color: ColorType> Sled<Color> {
         }
 
         for index in intersecting_indices {
-            let led = &mut self.leds[index];
-            led.color = color_rule(led);
+            self.update_led(index, |led| led.color = color_rule(led));
         }
 
         true
@@ -118,7 +315,7 @@ impl<Color: ColorType> Sled<Color> {
     ///
     /// O(SEGMENTS)
     pub fn set_at_dir(&mut self, dir: Vec2, color: Color) -> bool {
-        self.set_at_dir_from(dir, self.center_point, color)
+        self.set_at_dir_from(dir, self.layout.center_point, color)
     }
 
     /// Sets the color of each [LED](Led) in the given direction from a given point.
@@ -135,7 +332,7 @@ impl<Color: ColorType> Sled<Color> {
         }
 
         for index in intersecting_indices {
-            self.leds[index].color = color;
+            self.colors[index] = color;
         }
 
         true
@@ -200,7 +397,7 @@ impl<Color: ColorType> Sled<Color> {
         angle: f32,
         color_rule: F,
     ) -> bool {
-        self.modulate_at_angle_from(angle, self.center_point, color_rule)
+        self.modulate_at_angle_from(angle, self.layout.center_point, color_rule)
     }
 
     /// Modulates the color of each [LED](Led) whose direction relative to a point forms a given radian angle.
@@ -244,7 +441,7 @@ impl<Color: ColorType> Sled<Color> {
     ///
     /// O(SEGMENTS)
     pub fn set_at_angle(&mut self, angle: f32, color: Color) -> bool {
-        self.set_at_angle_from(angle, self.center_point, color)
+        self.set_at_angle_from(angle, self.layout.center_point, color)
     }
 
     /// Sets the color of each [LED](Led) whose direction relative to a point forms a given radian angle.
@@ -260,3 +457,8 @@ impl<Color: ColorType> Sled<Color> {
         self.set_at_dir_from(dir, pos, color)
     }
 }
+
+fn incidence_angle(surface: &LineSegment, dir: Vec2) -> f32 {
+    let normal = (surface.end - surface.start).perp().normalize_or_zero();
+    dir.dot(normal).abs().clamp(0.0, 1.0).acos()
+}
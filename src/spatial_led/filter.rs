@@ -1,12 +1,20 @@
-use alloc::collections::{btree_set, BTreeSet};
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
 
-use crate::{color::ColorType, led::Led, spatial_led::Sled};
+use crate::{color::ColorType, led::Led, rect::Rect, spatial_led::Sled, Vec2};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+const WORD_BITS: usize = u64::BITS as usize;
+
+#[derive(Clone, Default)]
 /// A Filter is a set of LEDs that can be obtained via one of [Sled's](Sled) getter-type methods.
 ///
 /// They are particularly useful for scenarios when you have computationally expensive calculations and you want to limit which LEDs those calculations are run on. Once you've created a filter, you can save it to [Data](crate::driver::Data) for use in draw/compute stages. Using this pattern, we can pre-compute important sets at startup and then store them to the driver for later usage.
 ///
+/// Internally, a Filter is a dense bitset with one bit per LED, so set operations like [Filter::and()] and [Filter::or()] work on 64 LEDs at a time, and memory use doesn't grow with the number of LEDs selected.
+///
 /// ```rust
 /// # use spatial_led::{Sled, Filter, Vec2, driver::{Driver, Data, Time}};
 /// # let mut driver = Driver::new();
@@ -28,110 +36,386 @@ use crate::{color::ColorType, led::Led, spatial_led::Sled};
 /// });
 /// ```
 pub struct Filter {
-    led_indices: BTreeSet<u16>,
+    words: Vec<u64>,
 }
 
-impl<Color: ColorType> From<&[Led<Color>]> for Filter {
-    fn from(value: &[Led<Color>]) -> Self {
-        let mut hs = BTreeSet::new();
+impl<Color: ColorType> From<&[Led<'_, Color>]> for Filter {
+    fn from(value: &[Led<'_, Color>]) -> Self {
+        let capacity = value.iter().map(|led| led.index() as usize + 1).max();
+        let mut filter = Filter::with_capacity(capacity.unwrap_or(0));
         for led in value {
-            hs.insert(led.index());
+            filter.insert(led.index());
         }
-        Filter { led_indices: hs }
+        filter
     }
 }
 
 impl From<BTreeSet<u16>> for Filter {
     fn from(value: BTreeSet<u16>) -> Self {
-        Filter { led_indices: value }
+        let capacity = value.last().map_or(0, |last| *last as usize + 1);
+        let mut filter = Filter::with_capacity(capacity);
+        filter.extend(value);
+        filter
     }
 }
 
-#[allow(dead_code)]
 impl Filter {
+    /// Creates an empty Filter with room for LEDs with indices up to `num_leds` without reallocating.
+    ///
+    /// Filters grow automatically as LEDs are inserted, so this is only a performance hint. Use [Sled::num_leds()] to size a Filter to your layout.
+    pub fn with_capacity(num_leds: usize) -> Self {
+        Filter {
+            words: vec![0; num_leds.div_ceil(WORD_BITS)],
+        }
+    }
+
     /// Returns the number of leds contained in the underlying set.
+    ///
+    /// O(LEDS / 64)
     pub fn len(&self) -> usize {
-        self.led_indices.len()
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
     }
 
     /// Returns true if the underlying set is empty.
+    ///
+    /// O(LEDS / 64)
     pub fn is_empty(&self) -> bool {
-        self.led_indices.is_empty()
+        self.words.iter().all(|w| *w == 0)
     }
 
-    /// Returns a new Filter containing all lEDs that were in both this and the inputted other Filter.
-    pub fn and(&self, other: &Self) -> Self {
-        let mut filtered = self.led_indices.clone();
-        for led in &self.led_indices {
-            if !other.led_indices.contains(led) {
-                filtered.remove(led);
+    /// Returns true if the LED at `index` is in the Filter.
+    ///
+    /// O(1)
+    pub fn contains(&self, index: u16) -> bool {
+        let (word, bit) = Filter::locate(index);
+        self.words.get(word).is_some_and(|w| w & bit != 0)
+    }
+
+    /// Adds the LED at `index` to the Filter. Returns true if it wasn't already present.
+    ///
+    /// O(1)
+    pub fn insert(&mut self, index: u16) -> bool {
+        let (word, bit) = Filter::locate(index);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let newly_inserted = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        newly_inserted
+    }
+
+    /// Removes the LED at `index` from the Filter. Returns true if it was present.
+    ///
+    /// O(1)
+    pub fn remove(&mut self, index: u16) -> bool {
+        let (word, bit) = Filter::locate(index);
+        match self.words.get_mut(word) {
+            Some(w) => {
+                let was_present = *w & bit != 0;
+                *w &= !bit;
+                was_present
             }
+            None => false,
         }
+    }
 
-        Filter {
-            led_indices: filtered,
+    /// Returns an Iterator over the indices of each LED in the Filter, in ascending order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            words: &self.words,
+            word_index: 0,
+            current: self.words.first().copied().unwrap_or(0),
         }
     }
 
+    /// Creates a Filter containing every LED index in `range`.
+    pub(crate) fn from_range(range: Range<usize>) -> Self {
+        let mut filter = Filter::with_capacity(range.end);
+        filter.extend(range.map(|i| i as u16));
+        filter
+    }
+
+    fn locate(index: u16) -> (usize, u64) {
+        let index = index as usize;
+        (index / WORD_BITS, 1 << (index % WORD_BITS))
+    }
+
+    fn combine(&self, other: &Self, op: impl Fn(u64, u64) -> u64) -> Self {
+        let len = self.words.len().max(other.words.len());
+        let words = (0..len)
+            .map(|i| {
+                let a = self.words.get(i).copied().unwrap_or(0);
+                let b = other.words.get(i).copied().unwrap_or(0);
+                op(a, b)
+            })
+            .collect();
+        Filter { words }
+    }
+
+    /// Returns a new Filter containing all lEDs that were in both this and the inputted other Filter.
+    ///
+    /// O(LEDS / 64)
+    pub fn and(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a & b)
+    }
+
     /// Returns a new Filter containing all lEDs that were in either this or the inputted other Filter.
+    ///
+    /// O(LEDS / 64)
     pub fn or(&self, other: &Self) -> Self {
-        let mut extended = self.led_indices.clone();
+        self.combine(other, |a, b| a | b)
+    }
 
-        for led in &other.led_indices {
-            extended.insert(*led);
+    /// Returns a new Filter containing all LEDs that were in this Filter but not in the inputted other Filter.
+    ///
+    /// O(LEDS / 64)
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter};
+    /// # use palette::rgb::Rgb;
+    /// # let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let ring: Filter = sled.within_dist(3.0).difference(&sled.within_dist(2.0));
+    /// ```
+    pub fn difference(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    /// Returns a new Filter containing all LEDs that were in exactly one of this and the inputted other Filter.
+    ///
+    /// O(LEDS / 64)
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    /// Returns a new Filter containing every LED in the [Sled] that is not in this Filter.
+    ///
+    /// O(LEDS / 64)
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let outside: Filter = sled.within_dist(2.0).complement(&sled);
+    /// sled.set_filter(&outside, Rgb::new(0.0, 0.0, 0.0));
+    /// ```
+    pub fn complement<Color: ColorType>(&self, sled: &Sled<Color>) -> Self {
+        let num_leds = sled.num_leds();
+        let mut complement = Filter::with_capacity(num_leds);
+        for (i, word) in complement.words.iter_mut().enumerate() {
+            *word = !self.words.get(i).copied().unwrap_or(0);
         }
 
-        Filter {
-            led_indices: extended,
+        let trailing_bits = num_leds % WORD_BITS;
+        if trailing_bits != 0 {
+            if let Some(last) = complement.words.last_mut() {
+                *last &= (1 << trailing_bits) - 1;
+            }
         }
+
+        complement
+    }
+
+    /// Returns a new Filter containing every `step`th LED of this Filter in index order, skipping the first `offset` LEDs.
+    ///
+    /// A `step` of 0 is treated as 1.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// // alternate between two colors along the first wall
+    /// let wall = sled.segment(0).unwrap();
+    /// sled.set_filter(&wall.stride(0, 2), Rgb::new(1.0, 0.0, 0.0));
+    /// sled.set_filter(&wall.stride(1, 2), Rgb::new(0.0, 0.0, 1.0));
+    /// ```
+    pub fn stride(&self, offset: usize, step: usize) -> Self {
+        let mut strided = Filter::with_capacity(self.words.len() * WORD_BITS);
+        strided.extend(self.iter().skip(offset).step_by(step.max(1)));
+        strided
+    }
+
+    /// Returns a new Filter containing every `n`th LED of this Filter in index order, starting with the first. Equivalent to `stride(0, n)`.
+    pub fn every_nth(&self, n: usize) -> Self {
+        self.stride(0, n)
+    }
+}
+
+impl PartialEq for Filter {
+    fn eq(&self, other: &Self) -> bool {
+        // capacities may differ, so treat missing words as empty
+        let len = self.words.len().max(other.words.len());
+        (0..len).all(|i| {
+            self.words.get(i).copied().unwrap_or(0) == other.words.get(i).copied().unwrap_or(0)
+        })
+    }
+}
+
+impl Eq for Filter {}
+
+impl fmt::Debug for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Pops the lowest set bit, moving on to later words once the current one is exhausted.
+fn next_set_bit(words: &[u64], word_index: &mut usize, current: &mut u64) -> Option<u16> {
+    while *current == 0 {
+        *word_index += 1;
+        *current = *words.get(*word_index)?;
+    }
+
+    let bit = current.trailing_zeros() as usize;
+    *current &= *current - 1;
+    Some((*word_index * WORD_BITS + bit) as u16)
+}
+
+/// An Iterator over the LED indices in a [Filter], in ascending order. See [Filter::iter()].
+#[derive(Clone, Debug)]
+pub struct Iter<'a> {
+    words: &'a [u64],
+    word_index: usize,
+    current: u64,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_set_bit(self.words, &mut self.word_index, &mut self.current)
+    }
+}
+
+/// An owning Iterator over the LED indices in a [Filter], in ascending order.
+#[derive(Clone, Debug)]
+pub struct IntoIter {
+    words: Vec<u64>,
+    word_index: usize,
+    current: u64,
+}
+
+impl Iterator for IntoIter {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_set_bit(&self.words, &mut self.word_index, &mut self.current)
     }
 }
 
 impl IntoIterator for Filter {
     type Item = u16;
-    type IntoIter = btree_set::IntoIter<u16>;
+    type IntoIter = IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.led_indices.into_iter()
+        IntoIter {
+            current: self.words.first().copied().unwrap_or(0),
+            words: self.words,
+            word_index: 0,
+        }
     }
 }
 
-impl IntoIterator for &Filter {
+impl<'a> IntoIterator for &'a Filter {
     type Item = u16;
-    type IntoIter = btree_set::IntoIter<u16>;
+    type IntoIter = Iter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
-        // this doesn't seem right; revisit
-        self.led_indices.clone().into_iter()
+        self.iter()
     }
 }
 
 impl FromIterator<u16> for Filter {
     fn from_iter<T: IntoIterator<Item = u16>>(iter: T) -> Self {
-        let mut set = BTreeSet::<u16>::new();
-        for i in iter {
-            set.insert(i);
-        }
-
-        Filter { led_indices: set }
+        let mut filter = Filter::default();
+        filter.extend(iter);
+        filter
     }
 }
 
 impl Extend<u16> for Filter {
     fn extend<T: IntoIterator<Item = u16>>(&mut self, iter: T) {
         for i in iter {
-            self.led_indices.insert(i);
+            self.insert(i);
         }
     }
 }
 
 impl<Color: ColorType> Sled<Color> {
+    /// Returns a bounding box around the LEDs in the given filter, or None if the filter is empty.
+    ///
+    /// O(LEDS_IN_FILTER)
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let left_half = sled.filter_by_pos(|pos| pos.x < 0.0);
+    /// let area = sled.filter_domain(&left_half).unwrap();
+    /// assert!(area.max.x < 0.0);
+    /// ```
+    pub fn filter_domain(&self, filter: &Filter) -> Option<Rect> {
+        Rect::from_points(
+            filter
+                .into_iter()
+                .map(|i| self.layout.leds[i as usize].position()),
+        )
+    }
+
+    /// Returns the indices of the LEDs in the given filter, ordered from closest to furthest from the center point.
+    ///
+    /// O(LEDS_IN_FILTER * log(LEDS_IN_FILTER))
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let wall = sled.segment(1).unwrap();
+    /// // light up the wall from the inside out
+    /// for (step, index) in sled.sort_filter_by_dist(&wall).into_iter().enumerate() {
+    ///     let brightness = 1.0 / (1.0 + step as f32 * 0.1);
+    ///     sled.set(index as usize, Rgb::new(brightness, brightness, brightness)).unwrap();
+    /// }
+    /// ```
+    pub fn sort_filter_by_dist(&self, filter: &Filter) -> Vec<u16> {
+        self.sort_filter_by(filter, |led| led.distance())
+    }
+
+    /// Returns the indices of the LEDs in the given filter, ordered from closest to furthest from `pos`.
+    ///
+    /// O(LEDS_IN_FILTER * log(LEDS_IN_FILTER))
+    pub fn sort_filter_by_dist_from(&self, filter: &Filter, pos: Vec2) -> Vec<u16> {
+        self.sort_filter_by(filter, |led| led.position().distance_squared(pos))
+    }
+
+    /// Returns the indices of the LEDs in the given filter, ordered by their [angle](Led::angle) around the center point.
+    ///
+    /// O(LEDS_IN_FILTER * log(LEDS_IN_FILTER))
+    pub fn sort_filter_by_angle(&self, filter: &Filter) -> Vec<u16> {
+        self.sort_filter_by(filter, |led| led.angle())
+    }
+
+    /// Returns the indices of the LEDs in the given filter, ordered by their [position along the whole path](Led::path_pos).
+    ///
+    /// O(LEDS_IN_FILTER * log(LEDS_IN_FILTER))
+    pub fn sort_filter_by_path_pos(&self, filter: &Filter) -> Vec<u16> {
+        self.sort_filter_by(filter, |led| led.path_pos())
+    }
+
+    fn sort_filter_by(&self, filter: &Filter, key: impl Fn(&Led<Color>) -> f32) -> Vec<u16> {
+        let mut keyed: Vec<(f32, u16)> = filter
+            .iter()
+            .map(|i| (key(&self.led(i as usize)), i))
+            .collect();
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+        keyed.into_iter().map(|(_, i)| i).collect()
+    }
+
     /// Sets all LEDs in the given filter to `color`.
     ///
     /// O(LEDS_IN_FILTER)
     pub fn set_filter(&mut self, filter: &Filter, color: Color) {
         for i in filter {
-            self.leds[i as usize].color = color;
+            self.colors[i as usize] = color;
         }
     }
 
@@ -153,16 +437,14 @@ impl<Color: ColorType> Sled<Color> {
     /// ```
     pub fn modulate_filter<F: Fn(&Led<Color>) -> Color>(&mut self, filter: &Filter, color_rule: F) {
         for i in filter {
-            let led = &mut self.leds[i as usize];
-            led.color = color_rule(led)
+            self.update_led(i as usize, |led| led.color = color_rule(led));
         }
     }
 
     /// Functionally identical to `modulate_filter()`.
     pub fn map_filter(&mut self, filter: &Filter, color_map: impl Fn(&Led<Color>) -> Color) {
         for i in filter {
-            let led = &mut self.leds[i as usize];
-            led.color = color_map(led)
+            self.update_led(i as usize, |led| led.color = color_map(led));
         }
     }
 
@@ -185,8 +467,7 @@ impl<Color: ColorType> Sled<Color> {
     /// ```
     pub fn for_each_in_filter<F: FnMut(&mut Led<Color>)>(&mut self, filter: &Filter, mut func: F) {
         for i in filter {
-            let led = &mut self.leds[i as usize];
-            func(led);
+            self.update_led(i as usize, &mut func);
         }
     }
 }
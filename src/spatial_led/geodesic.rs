@@ -0,0 +1,313 @@
+use alloc::collections::BinaryHeap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::{
+    color::ColorType,
+    led::Led,
+    spatial_led::{Filter, Sled},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+/// The along-the-strip distance from a set of source [LEDs](Led) to every LED in a [Sled].
+///
+/// Distances are measured by walking from LED to LED, crossing [junctions](crate::Junction) where segments meet, rather than in a straight line.
+/// LEDs that can't be reached from any source sit at a distance of `f32::INFINITY`.
+///
+/// Computing a PathField is relatively expensive, so if your sources don't move it's best to build it once and save it to [Data](crate::driver::Data).
+///
+/// ```rust
+/// # use spatial_led::{Sled, PathField, driver::{Driver, Data, Time}};
+/// # use palette::rgb::Rgb;
+/// # let mut driver = Driver::<Rgb>::new();
+/// driver.set_startup_commands(|sled, data| {
+///     let field: PathField = sled.path_field(&[0]);
+///     data.set("field", field);
+///     Ok(())
+/// });
+/// driver.set_draw_commands(|sled, data, time| {
+///     let field: &PathField = data.get("field")?;
+///     // a pulse that leaves LED 0 at 2 units per second
+///     let front = time.elapsed.as_secs_f32() * 2.0;
+///     sled.map_by_path_dist(field, |dist| {
+///         let brightness = (1.0 - (front - dist).abs()).max(0.0);
+///         Rgb::new(brightness, brightness, brightness)
+///     });
+///     Ok(())
+/// });
+/// ```
+pub struct PathField {
+    distances: Vec<f32>,
+}
+
+impl PathField {
+    /// Returns the along-the-strip distance from the nearest source to the [LED](Led) at `index`, if it exists.
+    pub fn get(&self, index: usize) -> Option<f32> {
+        self.distances.get(index).copied()
+    }
+
+    /// Returns an Iterator over the distance to each [LED](Led), in index order.
+    pub fn distances(&self) -> impl Iterator<Item = f32> + '_ {
+        self.distances.iter().copied()
+    }
+
+    /// Returns the greatest finite distance in the field. Useful for knowing when a pulse has reached every LED.
+    pub fn max_dist(&self) -> f32 {
+        self.distances
+            .iter()
+            .copied()
+            .filter(|d| d.is_finite())
+            .fold(0.0, f32::max)
+    }
+}
+
+#[derive(PartialEq)]
+struct Visit {
+    dist: f32,
+    index: usize,
+}
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    // reversed so that BinaryHeap pops the closest LED first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.dist.total_cmp(&self.dist)
+    }
+}
+
+/// # Along-the-strip (geodesic) distance methods.
+impl<Color: ColorType> Sled<Color> {
+    /// Returns a [PathField] holding the along-the-strip distance from the nearest of the given source [LEDs](Led) to every LED in the system.
+    ///
+    /// Source indices that don't exist are ignored.
+    ///
+    /// O(LEDS * log(LEDS))
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled};
+    ///# use palette::rgb::Rgb;
+    ///# let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let field = sled.path_field(&[0, 200]);
+    /// assert_eq!(field.get(0), Some(0.0));
+    /// ```
+    pub fn path_field(&self, sources: &[usize]) -> PathField {
+        let mut distances = vec![f32::INFINITY; self.layout.num_leds];
+        let mut frontier = BinaryHeap::new();
+
+        for source in sources {
+            if *source < self.layout.num_leds {
+                distances[*source] = 0.0;
+                frontier.push(Visit {
+                    dist: 0.0,
+                    index: *source,
+                });
+            }
+        }
+
+        while let Some(Visit { dist, index }) = frontier.pop() {
+            if dist > distances[index] {
+                continue;
+            }
+
+            let pos = self.layout.leds[index].position();
+            for neighbor in &self.layout.topology.led_neighbors[index] {
+                let neighbor = *neighbor as usize;
+                let candidate = dist + pos.distance(self.layout.leds[neighbor].position());
+                if candidate < distances[neighbor] {
+                    distances[neighbor] = candidate;
+                    frontier.push(Visit {
+                        dist: candidate,
+                        index: neighbor,
+                    });
+                }
+            }
+        }
+
+        PathField { distances }
+    }
+
+    /// Returns the along-the-strip distance between the [LEDs](Led) at indices `a` and `b`.
+    ///
+    /// Returns None if either LED doesn't exist or if there is no path between them.
+    ///
+    /// O(LEDS * log(LEDS))
+    pub fn path_dist_between(&self, a: usize, b: usize) -> Option<f32> {
+        if b >= self.layout.num_leds {
+            return None;
+        }
+
+        self.path_field(&[a]).get(b).filter(|dist| dist.is_finite())
+    }
+
+    /// Returns a [Filter] containing each [LED](Led) whose distance in the given [PathField] is less than `dist`.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Filter};
+    ///# use palette::rgb::Rgb;
+    ///# let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let field = sled.path_field(&[100]);
+    /// let nearby: Filter = sled.within_path_dist(&field, 1.5);
+    /// sled.set_filter(&nearby, Rgb::new(1.0, 1.0, 1.0));
+    /// ```
+    pub fn within_path_dist(&self, field: &PathField, dist: f32) -> Filter {
+        field
+            .distances
+            .iter()
+            .enumerate()
+            .filter_map(|(i, d)| if *d < dist { Some(i as u16) } else { None })
+            .collect()
+    }
+
+    /// Modulates the color of each [LED](Led) whose distance in the given [PathField] is less than `dist`.
+    ///
+    /// Returns true if any LEDs were modulated, false otherwise.
+    ///
+    /// O(LEDS)
+    pub fn modulate_within_path_dist<F: Fn(&Led<Color>) -> Color>(
+        &mut self,
+        field: &PathField,
+        dist: f32,
+        color_rule: F,
+    ) -> bool {
+        let mut changes_made = false;
+
+        self.update_all(|led| {
+            let d = field.distances.get(led.index() as usize);
+            if d.is_some_and(|d| *d < dist) {
+                led.color = color_rule(led);
+                changes_made = true;
+            }
+        });
+
+        changes_made
+    }
+
+    /// Sets the color of each [LED](Led) whose distance in the given [PathField] is less than `dist`.
+    ///
+    /// Returns true if any LEDs were set, false otherwise.
+    ///
+    /// O(LEDS)
+    pub fn set_within_path_dist(&mut self, field: &PathField, dist: f32, color: Color) -> bool {
+        let mut changes_made = false;
+
+        for (led_color, d) in self.colors.iter_mut().zip(&field.distances) {
+            if *d < dist {
+                *led_color = color;
+                changes_made = true;
+            }
+        }
+
+        changes_made
+    }
+
+    /// Maps LEDs to a color depending on their distance in the given [PathField].
+    ///
+    /// Unreachable LEDs are passed a distance of `f32::INFINITY`.
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled};
+    ///# use palette::rgb::Rgb;
+    ///# let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let field = sled.path_field(&[0]);
+    /// sled.map_by_path_dist(&field, |dist| {
+    ///     let transformed = (1.0 - (dist / 5.0)).max(0.0);
+    ///     Rgb::new(transformed, 0.0, 0.0)
+    /// });
+    /// ```
+    pub fn map_by_path_dist(
+        &mut self,
+        field: &PathField,
+        dist_to_color_map: impl Fn(f32) -> Color,
+    ) {
+        for (led_color, d) in self.colors.iter_mut().zip(&field.distances) {
+            *led_color = dist_to_color_map(*d);
+        }
+    }
+
+    /// Returns a [Filter] containing all LEDs whose distance in the given [PathField] meets a certain criteria.
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled};
+    ///# use palette::rgb::Rgb;
+    ///# let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let field = sled.path_field(&[0]);
+    /// let band = sled.filter_by_path_dist(&field, |dist| dist > 2.0 && dist < 2.5);
+    /// sled.set_filter(&band, Rgb::new(1.0, 1.0, 1.0));
+    /// ```
+    pub fn filter_by_path_dist(
+        &self,
+        field: &PathField,
+        dist_filter: impl Fn(f32) -> bool,
+    ) -> Filter {
+        field
+            .distances
+            .iter()
+            .enumerate()
+            .filter_map(|(i, d)| {
+                if dist_filter(*d) {
+                    Some(i as u16)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+impl<Color: ColorType> Sled<Color> {
+    /// Appends every LED within `radius` of the LED at `index` along the strip to `found`, along with its distance. The LED itself is included at a distance of 0.
+    ///
+    /// `distances` must hold `f32::INFINITY` for every LED, and is restored to that state before returning, so it can be reused across calls.
+    ///
+    /// O(N * log(N)), where N is the number of LEDs found.
+    pub(crate) fn path_neighborhood(
+        &self,
+        index: usize,
+        radius: f32,
+        distances: &mut [f32],
+        found: &mut Vec<(usize, f32)>,
+    ) {
+        let first = found.len();
+        let mut frontier = BinaryHeap::new();
+        distances[index] = 0.0;
+        found.push((index, 0.0));
+        frontier.push(Visit { dist: 0.0, index });
+
+        while let Some(Visit { dist, index }) = frontier.pop() {
+            if dist > distances[index] {
+                continue;
+            }
+
+            let pos = self.layout.leds[index].position();
+            for neighbor in &self.layout.topology.led_neighbors[index] {
+                let neighbor = *neighbor as usize;
+                let candidate = dist + pos.distance(self.layout.leds[neighbor].position());
+                if candidate <= radius && candidate < distances[neighbor] {
+                    if distances[neighbor].is_infinite() {
+                        found.push((neighbor, candidate));
+                    }
+                    distances[neighbor] = candidate;
+                    frontier.push(Visit {
+                        dist: candidate,
+                        index: neighbor,
+                    });
+                }
+            }
+        }
+
+        for (i, dist) in &mut found[first..] {
+            *dist = distances[*i];
+            distances[*i] = f32::INFINITY;
+        }
+    }
+}
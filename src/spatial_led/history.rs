@@ -0,0 +1,205 @@
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{
+    color::{Blend, BlendMode, ColorType},
+    error::SledError,
+    spatial_led::Sled,
+};
+
+#[derive(Clone, Debug)]
+/// A ring buffer of previously recorded color frames.
+pub(crate) struct History<COLOR> {
+    frames: Vec<Vec<COLOR>>,
+    /// Index of the most recently recorded frame.
+    newest: usize,
+    /// Number of frames recorded so far, up to `frames.len()`.
+    recorded: usize,
+}
+
+impl<COLOR> Default for History<COLOR> {
+    fn default() -> Self {
+        History {
+            frames: Vec::new(),
+            newest: 0,
+            recorded: 0,
+        }
+    }
+}
+
+/// # Frame history
+///
+/// A Sled can keep copies of the colors it held in previous frames, for effects like trails and motion blur.
+/// History is off by default; turn it on with [Sled::set_history_len()].
+impl<COLOR: ColorType> Sled<COLOR> {
+    /// Sets how many previous frames the Sled keeps, clearing any it already recorded. A length of 0 turns history off.
+    ///
+    /// Memory for every frame is allocated here, so [recording](Sled::record_frame) never allocates.
+    ///
+    /// O(LEDS * FRAMES)
+    ///
+    /// ```rust
+    ///# use spatial_led::Sled;
+    ///# let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// sled.set_history_len(2);
+    ///
+    /// sled.set_all((1.0, 0.0, 0.0));
+    /// sled.record_frame();
+    /// sled.set_all((0.0, 1.0, 0.0));
+    /// sled.record_frame();
+    /// sled.set_all((0.0, 0.0, 1.0));
+    ///
+    /// assert_eq!(sled.previous_color(0, 1), Some((0.0, 1.0, 0.0)));
+    /// assert_eq!(sled.previous_color(0, 2), Some((1.0, 0.0, 0.0)));
+    /// assert_eq!(sled.previous_color(0, 3), None);
+    /// ```
+    pub fn set_history_len(&mut self, frames: usize) {
+        self.history = History {
+            frames: vec![vec![COLOR::default(); self.layout.num_leds]; frames],
+            newest: 0,
+            recorded: 0,
+        };
+    }
+
+    /// Returns how many previous frames the Sled keeps.
+    ///
+    /// O(1)
+    pub fn history_len(&self) -> usize {
+        self.history.frames.len()
+    }
+
+    /// Saves a copy of the Sled's current colors as the most recent frame in its history, discarding the oldest one if the history is full.
+    /// Does nothing if history is off.
+    ///
+    /// [Drivers](crate::driver::Driver) call this automatically at the start of each step, so from inside draw commands, `previous_frame(1)` is whatever was drawn the step before.
+    ///
+    /// O(LEDS)
+    pub fn record_frame(&mut self) {
+        let history = &mut self.history;
+        if history.frames.is_empty() {
+            return;
+        }
+
+        history.newest = (history.newest + 1) % history.frames.len();
+        history.frames[history.newest].copy_from_slice(&self.colors);
+        history.recorded = (history.recorded + 1).min(history.frames.len());
+    }
+
+    /// Returns the colors the Sled held `frames_ago` recordings ago, where 1 is the most recently [recorded](Sled::record_frame) frame.
+    ///
+    /// Returns None if `frames_ago` is 0, or further back than the history goes.
+    ///
+    /// O(1)
+    pub fn previous_frame(&self, frames_ago: usize) -> Option<&[COLOR]> {
+        let history = &self.history;
+        if frames_ago == 0 || frames_ago > history.recorded {
+            return None;
+        }
+
+        let len = history.frames.len();
+        let index = (history.newest + len - (frames_ago - 1)) % len;
+        Some(&history.frames[index])
+    }
+
+    /// Returns the color the [LED](crate::Led) at `index` held `frames_ago` recordings ago. See [Sled::previous_frame()].
+    ///
+    /// O(1)
+    pub fn previous_color(&self, index: usize, frames_ago: usize) -> Option<COLOR> {
+        self.previous_frame(frames_ago)?.get(index).copied()
+    }
+
+    /// Forgets every recorded frame, without changing how many the Sled keeps.
+    ///
+    /// O(1)
+    pub fn clear_history(&mut self) {
+        self.history.recorded = 0;
+    }
+
+    /// Returns the index of the most recently recorded frame, or an error if there isn't one.
+    fn newest_frame_index(&self) -> Result<usize, SledError> {
+        match self.history.recorded {
+            0 => SledError::new(format!(
+                "No previous frame has been recorded (history length is {}).",
+                self.history_len()
+            ))
+            .as_err(),
+            _ => Ok(self.history.newest),
+        }
+    }
+}
+
+/// # Temporal effects
+impl<Color: ColorType + Blend> Sled<Color> {
+    /// Moves every [LED](crate::Led) a fraction `rate` of the way toward `target`. Called once per frame, colors decay exponentially.
+    ///
+    /// Unlike scaling colors with `led.color * 0.95`, this works for any color type that can be [mixed](Blend::mix), and can decay toward colors other than black.
+    /// To keep the decay speed independent of frame rate, derive `rate` from the time between frames: `1.0 - 0.5f32.powf(delta / half_life)`.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    ///# use spatial_led::Sled;
+    ///# let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap").unwrap();
+    /// sled.set_all((1.0, 1.0, 1.0));
+    /// sled.decay_toward((0.0, 0.0, 0.5), 0.5);
+    /// assert_eq!(sled.get(0).unwrap().color, (0.5, 0.5, 0.75));
+    /// ```
+    pub fn decay_toward(&mut self, target: Color, rate: f32) {
+        let rate = rate.clamp(0.0, 1.0);
+        for color in self.colors.iter_mut() {
+            *color = color.mix(target, rate);
+        }
+    }
+
+    /// Gives every [LED](crate::Led) an afterglow: wherever the previous frame was brighter than the current one, the LED shows the previous color instead, faded a fraction `fade` of the way toward `Color::default()`.
+    ///
+    /// Lit LEDs stay lit, and LEDs that were just switched off fade out over the next few frames rather than cutting to black.
+    ///
+    /// Returns an [error](SledError) if no previous frame has been [recorded](Sled::record_frame).
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, SledError, Vec2};
+    ///# fn demo() -> Result<(), SledError> {
+    ///# let mut sled = Sled::<(f32, f32, f32)>::new("./benches/config.yap")?;
+    /// sled.set_history_len(1);
+    /// // each frame: record, clear, draw a moving dot, then let the old dots glow
+    /// sled.record_frame();
+    /// sled.set_all((0.0, 0.0, 0.0));
+    /// sled.set_within_dist_from(0.3, Vec2::new(1.0, 0.0), (1.0, 0.6, 0.2));
+    /// sled.afterglow(0.1)?;
+    ///# Ok(())
+    ///# }
+    /// ```
+    pub fn afterglow(&mut self, fade: f32) -> Result<(), SledError> {
+        let fade = fade.clamp(0.0, 1.0);
+        let frame = self.newest_frame_index()?;
+
+        for (color, previous) in self.colors.iter_mut().zip(&self.history.frames[frame]) {
+            let faded = previous.mix(Color::default(), fade);
+            *color = faded.blend(*color, BlendMode::Lighten);
+        }
+
+        Ok(())
+    }
+
+    /// Blends every [LED](crate::Led) from its color in the previous frame toward its current color by `amount`, smoothing out changes between frames like motion blur.
+    ///
+    /// An `amount` of 1 keeps the current frame as it is, 0 repeats the previous one.
+    ///
+    /// Returns an [error](SledError) if no previous frame has been [recorded](Sled::record_frame).
+    ///
+    /// O(LEDS)
+    pub fn lerp_from_previous(&mut self, amount: f32) -> Result<(), SledError> {
+        let amount = amount.clamp(0.0, 1.0);
+        let frame = self.newest_frame_index()?;
+
+        for (color, previous) in self.colors.iter_mut().zip(&self.history.frames[frame]) {
+            *color = previous.mix(*color, amount);
+        }
+
+        Ok(())
+    }
+}
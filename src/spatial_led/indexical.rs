@@ -15,8 +15,9 @@ impl<COLOR: ColorType> Sled<COLOR> {
     /// Returns `Some(&Led<COLOR>)` if an [LED](Led) at `index` exists, `None` if not.
     ///
     /// O(1)
-    pub fn get(&self, index: usize) -> Option<&Led<COLOR>> {
-        self.leds.get(index)
+    pub fn get(&self, index: usize) -> Option<Led<'_, COLOR>> {
+        let geometry = self.layout.leds.get(index)?;
+        Some(Led::new(self.colors[index], geometry))
     }
 
     /// Modulates the color of the [LED](Led) at `index` given a color rule function.
@@ -40,12 +41,11 @@ impl<COLOR: ColorType> Sled<COLOR> {
         index: usize,
         color_rule: F,
     ) -> Result<(), SledError> {
-        if index >= self.num_leds {
+        if index >= self.layout.num_leds {
             return SledError::new(format!("LED at index {} does not exist.", index)).as_err();
         }
 
-        let led = &mut self.leds[index];
-        led.color = color_rule(led);
+        self.update_led(index, |led| led.color = color_rule(led));
         Ok(())
     }
 
@@ -55,11 +55,11 @@ impl<COLOR: ColorType> Sled<COLOR> {
     /// O(1)
     ///
     pub fn set(&mut self, index: usize, color: COLOR) -> Result<(), SledError> {
-        if index >= self.num_leds {
+        if index >= self.layout.num_leds {
             return SledError::new(format!("LED at index {} does not exist.", index)).as_err();
         }
 
-        self.leds[index].color = color;
+        self.colors[index] = color;
         Ok(())
     }
 
@@ -68,9 +68,7 @@ impl<COLOR: ColorType> Sled<COLOR> {
     /// O(LEDS)
     ///
     pub fn set_all(&mut self, color: COLOR) {
-        for led in &mut self.leds {
-            led.color = color;
-        }
+        self.colors.fill(color);
     }
 
     /// For each method that grants mutable access to each [LED](Led) in the system.
@@ -89,10 +87,8 @@ impl<COLOR: ColorType> Sled<COLOR> {
     ///     }
     /// });
     /// ```
-    pub fn for_each<F: FnMut(&mut Led<COLOR>)>(&mut self, mut func: F) {
-        for led in self.leds.iter_mut() {
-            func(led);
-        }
+    pub fn for_each<F: FnMut(&mut Led<COLOR>)>(&mut self, func: F) {
+        self.update_all(func);
     }
 }
 
@@ -104,9 +100,8 @@ impl<COLOR: ColorType> Sled<COLOR> {
     /// O(RANGE_SIZE)
     ///
     pub fn range(&self, index_range: Range<usize>) -> Option<Filter> {
-        if index_range.end < self.num_leds {
-            let led_range = &self.leds[index_range];
-            Some(led_range.into())
+        if index_range.end < self.layout.num_leds {
+            Some(Filter::from_range(index_range))
         } else {
             None
         }
@@ -131,14 +126,12 @@ impl<COLOR: ColorType> Sled<COLOR> {
         index_range: Range<usize>,
         color_rule: F,
     ) -> Result<(), SledError> {
-        if index_range.end >= self.num_leds {
+        if index_range.end >= self.layout.num_leds {
             return SledError::new("Index range extends beyond size of system.".to_string())
                 .as_err();
         }
 
-        for led in &mut self.leds[index_range] {
-            led.color = color_rule(led);
-        }
+        self.update_leds(index_range, |led| led.color = color_rule(led));
 
         Ok(())
     }
@@ -149,14 +142,12 @@ impl<COLOR: ColorType> Sled<COLOR> {
     /// O(RANGE_SIZE)
     ///
     pub fn set_range(&mut self, index_range: Range<usize>, color: COLOR) -> Result<(), SledError> {
-        if index_range.end >= self.num_leds {
+        if index_range.end >= self.layout.num_leds {
             return SledError::new("Index range extends beyond size of system.".to_string())
                 .as_err();
         }
 
-        self.leds[index_range]
-            .iter_mut()
-            .for_each(|led| led.color = color);
+        self.colors[index_range].fill(color);
         Ok(())
     }
 
@@ -183,11 +174,11 @@ impl<COLOR: ColorType> Sled<COLOR> {
         index_range: Range<usize>,
         func: F,
     ) -> Result<(), SledError> {
-        if index_range.end >= self.num_leds {
+        if index_range.end >= self.layout.num_leds {
             return SledError::new("Index range extends beyond size of system.".to_string())
                 .as_err();
         }
-        self.leds[index_range].iter_mut().for_each(func);
+        self.update_leds(index_range, func);
         Ok(())
     }
 }
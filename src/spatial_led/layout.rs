@@ -0,0 +1,377 @@
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::float::Float as _;
+
+use crate::{
+    config::{Config, LineSegment},
+    error::SledError,
+    led::LedGeometry,
+    output::Calibration,
+    rect::Rect,
+    spatial_led::Filter,
+    spatial_led::{Layout, Topology},
+    Vec2,
+};
+
+/// # Construction and basic layout info
+impl Layout {
+    /// Constructs a new Layout given the path to a config file. See [Sled::new()](crate::Sled::new) for the file format.
+    ///
+    /// This is an expensive operation, as everything the Sled needs to answer spatial queries is pre-calculated here.
+    #[cfg(feature = "std")]
+    pub fn new(config_file_path: &str) -> Result<Self, SledError> {
+        let config = Config::from_toml_file(config_file_path)?;
+        Layout::new_from_config(config)
+    }
+
+    /// Works like [Layout::new()] but rather than reading the contents of a config file from disk, allows you to pass in the same information as a `&str`.
+    pub fn new_from_str(string: &str) -> Result<Self, SledError> {
+        let config = Config::from_str(string)?;
+        Layout::new_from_config(config)
+    }
+
+    pub(crate) fn new_from_config(config: Config) -> Result<Self, SledError> {
+        let leds_per_segment = Layout::leds_per_segment(&config);
+        let (segment_chain_offsets, chain_lengths) = Layout::chain_arc_lengths(&config);
+        let mut leds = Layout::build_led_list(
+            &leds_per_segment,
+            &config.line_segments,
+            &segment_chain_offsets,
+            &config.center_point,
+        );
+        let line_segment_endpoint_indices =
+            Layout::line_segment_endpoint_indices(&leds_per_segment);
+        let chain_endpoint_indices =
+            Layout::chain_endpoint_indices(&config, &line_segment_endpoint_indices);
+        let path_length = chain_lengths.iter().sum();
+        let vertex_indices = Layout::vertex_indices(&config);
+        let num_leds = leds.len();
+        let index_of_closest = leds
+            .iter()
+            .min_by(|l, r| l.distance().partial_cmp(&r.distance()).unwrap())
+            .unwrap()
+            .index() as usize;
+
+        let index_of_furthest = leds
+            .iter()
+            .max_by(|l, r| l.distance().partial_cmp(&r.distance()).unwrap())
+            .unwrap()
+            .index() as usize;
+
+        let domain = Layout::calc_domain(&leds);
+        Layout::assign_normalized_coords(&mut leds, &domain, index_of_furthest);
+        let topology = Topology::new(
+            &config.line_segments,
+            &line_segment_endpoint_indices,
+            num_leds,
+        );
+        Layout::assign_orientations(
+            &mut leds,
+            &config,
+            &line_segment_endpoint_indices,
+            &topology,
+        );
+
+        Ok(Layout {
+            center_point: config.center_point,
+            leds,
+            num_leds,
+            density: config.density,
+            line_segments: config.line_segments,
+            chain_lengths,
+            path_length,
+            index_of_closest,
+            index_of_furthest,
+            domain,
+            // utility lookup tables
+            line_segment_endpoint_indices,
+            chain_endpoint_indices,
+            vertex_indices,
+            topology,
+            calibrations: Vec::new(),
+        })
+    }
+
+    fn leds_per_segment(config: &Config) -> Vec<usize> {
+        config
+            .line_segments
+            .iter()
+            .map(|line| line.num_leds(config.density))
+            .collect()
+    }
+
+    fn build_led_list(
+        leds_per_segment: &[usize],
+        line_segments: &[LineSegment],
+        segment_chain_offsets: &[f32],
+        center_point: &Vec2,
+    ) -> Vec<LedGeometry> {
+        let mut leds = vec![];
+        let mut segment_path_offset = 0.0;
+
+        for (segment_index, segment_size) in leds_per_segment.iter().enumerate() {
+            let segment = &line_segments[segment_index];
+            let length = segment.length();
+            for i in 0..*segment_size {
+                let alpha = (i + 1) as f32 / *segment_size as f32;
+                let pos = segment.start.lerp(segment.end, alpha);
+                let led = LedGeometry::new(
+                    pos,
+                    leds.len() as u16,
+                    segment_index as u8,
+                    *center_point,
+                    segment_chain_offsets[segment_index] + length * alpha,
+                    segment_path_offset + length * alpha,
+                );
+
+                leds.push(led);
+            }
+            segment_path_offset += length;
+        }
+        leds
+    }
+
+    /// Computes each LED's tangent and normal, blending tangents across corners where exactly two segments meet.
+    fn assign_orientations(
+        leds: &mut [LedGeometry],
+        config: &Config,
+        line_segment_endpoint_indices: &[(usize, usize)],
+        topology: &Topology,
+    ) {
+        let spacing = config.density.recip();
+        let segment_dir = |segment: &LineSegment| (segment.end - segment.start).normalize_or_zero();
+
+        for (segment_index, segment) in config.line_segments.iter().enumerate() {
+            let (start, end) = line_segment_endpoint_indices[segment_index];
+            let (start_node, end_node) = topology.segment_nodes[segment_index];
+            let dir = segment_dir(segment);
+            let length = segment.length();
+            let num_leds = end - start;
+
+            // direction of the segment continuing on from each endpoint, oriented to flow through this one.
+            let neighbor_dir = |node: usize, leaving: bool| {
+                let junction = &topology.nodes[node];
+                if junction.degree() != 2 {
+                    return None;
+                }
+                let other = *junction.segments().iter().find(|s| **s != segment_index)?;
+                let other_dir = segment_dir(&config.line_segments[other]);
+                let other_starts_here = topology.segment_nodes[other].0 == node;
+                Some(if other_starts_here == leaving {
+                    other_dir
+                } else {
+                    -other_dir
+                })
+            };
+            let next_dir = neighbor_dir(end_node, true);
+            let prev_dir = neighbor_dir(start_node, false);
+
+            for (k, led) in leds[start..end].iter_mut().enumerate() {
+                let along = length * (k + 1) as f32 / num_leds as f32;
+                let mut tangent = dir;
+
+                if let Some(next_dir) = next_dir {
+                    let to_end = length - along;
+                    if to_end < spacing {
+                        tangent = tangent.lerp(next_dir, 0.5 * (1.0 - to_end / spacing));
+                    }
+                }
+
+                if let Some(prev_dir) = prev_dir {
+                    if along < spacing {
+                        tangent = tangent.lerp(prev_dir, 0.5 * (1.0 - along / spacing));
+                    }
+                }
+
+                let tangent = tangent.try_normalize().unwrap_or(dir);
+                let mut normal = tangent.perp();
+                if normal.dot(led.position() - config.center_point) < 0.0 {
+                    normal = -normal;
+                }
+                led.set_orientation(tangent, normal);
+            }
+        }
+    }
+
+    fn assign_normalized_coords(leds: &mut [LedGeometry], domain: &Rect, index_of_furthest: usize) {
+        let size = domain.size();
+        let side = size.max_element();
+        let inv_side = if side > 0.0 { side.recip() } else { 0.0 };
+        // centers the shorter axis within the square
+        let square_offset = (Vec2::splat(side) - size) * 0.5;
+
+        let furthest = leds[index_of_furthest].distance();
+        let inv_furthest = if furthest > 0.0 {
+            furthest.recip()
+        } else {
+            0.0
+        };
+
+        for led in leds {
+            let local = led.position() - domain.min;
+            led.set_normalized_coords(
+                domain.uv_of(led.position()),
+                (local + square_offset) * inv_side,
+                led.distance() * inv_furthest,
+            );
+        }
+    }
+
+    /// Returns the arc length at which each segment begins within its chain, and the total length of each chain.
+    fn chain_arc_lengths(config: &Config) -> (Vec<f32>, Vec<f32>) {
+        let mut segment_chain_offsets = vec![0.0; config.line_segments.len()];
+        let mut chain_lengths = Vec::with_capacity(config.chains.len());
+
+        for chain in &config.chains {
+            let mut chain_length = 0.0;
+            for segment_index in chain.clone() {
+                segment_chain_offsets[segment_index] = chain_length;
+                chain_length += config.line_segments[segment_index].length();
+            }
+            chain_lengths.push(chain_length);
+        }
+
+        (segment_chain_offsets, chain_lengths)
+    }
+
+    fn chain_endpoint_indices(
+        config: &Config,
+        line_segment_endpoint_indices: &[(usize, usize)],
+    ) -> Vec<(usize, usize)> {
+        config
+            .chains
+            .iter()
+            .map(|chain| {
+                let (start, _) = line_segment_endpoint_indices[chain.start];
+                let (_, end) = line_segment_endpoint_indices[chain.end - 1];
+                (start, end)
+            })
+            .collect()
+    }
+
+    fn line_segment_endpoint_indices(leds_per_segment: &Vec<usize>) -> Vec<(usize, usize)> {
+        let mut line_segment_endpoint_indices = vec![];
+        let mut last_index = 0;
+        for num_leds in leds_per_segment {
+            line_segment_endpoint_indices.push((last_index, last_index + num_leds));
+            last_index += num_leds;
+        }
+
+        line_segment_endpoint_indices
+    }
+
+    fn vertex_indices(config: &Config) -> Vec<usize> {
+        let mut vertex_indices = vec![];
+
+        let start = config.line_segments[0].start;
+        let end = config.line_segments[config.line_segments.len() - 1].end;
+
+        if start != end {
+            vertex_indices.push(0);
+        }
+
+        let mut last_end_point: Vec2 = start;
+        let mut last_index = 0;
+        for line in &config.line_segments {
+            if line.start != last_end_point {
+                vertex_indices.push(last_index);
+            }
+
+            let num_leds = line.num_leds(config.density);
+            vertex_indices.push(last_index + num_leds - 1);
+
+            last_index += num_leds;
+            last_end_point = line.end;
+        }
+
+        vertex_indices
+    }
+
+    fn calc_domain(leds: &[LedGeometry]) -> Rect {
+        Rect::from_points(leds.iter().map(|led| led.position())).unwrap_or_default()
+    }
+
+    /// Returns the total number of LEDs in the Layout.
+    ///
+    /// O(1)
+    pub fn num_leds(&self) -> usize {
+        self.num_leds
+    }
+
+    /// Returns the static reference point declared in the [config file](crate::Sled::new).
+    ///
+    /// O(1)
+    pub fn center_point(&self) -> Vec2 {
+        self.center_point
+    }
+
+    /// Returns a bounding box around the LEDs.
+    ///
+    /// O(1)
+    pub fn domain(&self) -> Rect {
+        self.domain
+    }
+}
+
+/// # Output calibration
+impl Layout {
+    /// Returns the [Calibration] of the LED at `index`. LEDs that were never calibrated return [Calibration::IDENTITY].
+    ///
+    /// O(1)
+    pub fn calibration(&self, index: usize) -> Calibration {
+        self.calibrations.get(index).copied().unwrap_or_default()
+    }
+
+    /// Returns the [Calibration] of every LED, in index order, or an empty slice if none have been calibrated.
+    ///
+    /// O(1)
+    pub fn calibrations(&self) -> &[Calibration] {
+        &self.calibrations
+    }
+
+    /// Sets the [Calibration] of each LED in `filter`, replacing any it had before.
+    ///
+    /// O(LEDS_IN_FILTER)
+    pub fn set_calibration(&mut self, filter: &Filter, calibration: Calibration) {
+        if self.calibrations.is_empty() {
+            self.calibrations = vec![Calibration::IDENTITY; self.num_leds];
+        }
+
+        for i in filter {
+            if let Some(led_calibration) = self.calibrations.get_mut(i as usize) {
+                *led_calibration = calibration;
+            }
+        }
+    }
+
+    /// Sets the [Calibration] of each LED on the line segment with index `segment_index`.
+    /// Returns an [error](SledError) if no line segment with the given index exists.
+    ///
+    /// O(LEDS_IN_SEGMENT)
+    pub fn set_segment_calibration(
+        &mut self,
+        segment_index: usize,
+        calibration: Calibration,
+    ) -> Result<(), SledError> {
+        let Some((start, end)) = self.line_segment_endpoint_indices.get(segment_index) else {
+            return SledError::new(format!(
+                "No line segment of index {} exists.",
+                segment_index
+            ))
+            .as_err();
+        };
+
+        self.set_calibration(&Filter::from_range(*start..*end), calibration);
+        Ok(())
+    }
+
+    /// Resets every LED to [Calibration::IDENTITY].
+    ///
+    /// O(1)
+    pub fn clear_calibration(&mut self) {
+        self.calibrations = Vec::new();
+    }
+}
@@ -1,5 +1,3 @@
-use alloc::collections::BTreeSet;
-
 #[cfg(not(feature = "std"))]
 use num_traits::float::Float as _;
 
@@ -27,9 +25,7 @@ impl<Color: ColorType> Sled<Color> {
     /// });
     /// ```
     pub fn map(&mut self, led_to_color_map: impl Fn(&Led<Color>) -> Color) {
-        self.leds
-            .iter_mut()
-            .for_each(|led| led.color = led_to_color_map(led));
+        self.update_all(|led| led.color = led_to_color_map(led));
     }
 
     /// Maps LED indices to a color.
@@ -122,7 +118,7 @@ impl<Color: ColorType> Sled<Color> {
     /// });
     /// ```
     pub fn map_by_dir_from(&mut self, point: Vec2, dir_to_color_map: impl Fn(Vec2) -> Color) {
-        self.leds.iter_mut().for_each(|led| {
+        self.update_all(|led| {
             let dir = (point - led.position()).normalize_or_zero();
             led.color = dir_to_color_map(dir)
         });
@@ -141,7 +137,7 @@ impl<Color: ColorType> Sled<Color> {
     /// });
     /// ```
     pub fn map_by_angle(&mut self, angle_to_color_map: impl Fn(f32) -> Color) {
-        self.leds.iter_mut().for_each(|led| {
+        self.update_all(|led| {
             led.color = angle_to_color_map(led.angle());
         });
     }
@@ -159,7 +155,7 @@ impl<Color: ColorType> Sled<Color> {
     /// });
     /// ```
     pub fn map_by_angle_from(&mut self, point: Vec2, angle_to_color_map: impl Fn(f32) -> Color) {
-        self.leds.iter_mut().for_each(|led| {
+        self.update_all(|led| {
             let delta = point - led.position();
             let angle = delta.x.atan2(delta.y);
             led.color = angle_to_color_map(angle);
@@ -179,9 +175,7 @@ impl<Color: ColorType> Sled<Color> {
     /// });
     /// ```
     pub fn map_by_dist(&mut self, dist_to_color_map: impl Fn(f32) -> Color) {
-        self.leds
-            .iter_mut()
-            .for_each(|led| led.color = dist_to_color_map(led.distance()));
+        self.update_all(|led| led.color = dist_to_color_map(led.distance()));
     }
 
     /// Maps LEDs to a color depending on their distance from the given point.
@@ -197,11 +191,126 @@ impl<Color: ColorType> Sled<Color> {
     /// });
     /// ```
     pub fn map_by_dist_from(&mut self, pos: Vec2, dist_to_color_map: impl Fn(f32) -> Color) {
-        self.leds.iter_mut().for_each(|led| {
+        self.update_all(|led| {
             let dist = pos.distance(led.position());
             led.color = dist_to_color_map(dist);
         });
     }
+
+    /// Maps LEDs to a color depending on their position within the Sled's [domain](Sled::domain). See [Led::uv()].
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// sled.map_by_uv(|uv| Rgb::new(uv.x, uv.y, 0.5));
+    /// ```
+    pub fn map_by_uv(&mut self, uv_to_color_map: impl Fn(Vec2) -> Color) {
+        self.update_all(|led| led.color = uv_to_color_map(led.uv()));
+    }
+
+    /// Maps LEDs to a color depending on their position within the Sled's [domain](Sled::domain), preserving its aspect ratio. See [Led::square_uv()].
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// // a circle that stays round no matter the shape of the layout
+    /// sled.map_by_square_uv(|uv| {
+    ///     let inside = uv.distance(Vec2::splat(0.5)) < 0.3;
+    ///     if inside { Rgb::new(1.0, 1.0, 1.0) } else { Rgb::new(0.0, 0.0, 0.0) }
+    /// });
+    /// ```
+    pub fn map_by_square_uv(&mut self, uv_to_color_map: impl Fn(Vec2) -> Color) {
+        self.update_all(|led| led.color = uv_to_color_map(led.square_uv()));
+    }
+
+    /// Maps LEDs to a color depending on their [normalized distance](Led::normalized_distance) from the `center_point`.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// sled.map_by_normalized_dist(|dist| Rgb::new(1.0 - dist, 0.0, dist));
+    /// ```
+    pub fn map_by_normalized_dist(&mut self, dist_to_color_map: impl Fn(f32) -> Color) {
+        self.update_all(|led| led.color = dist_to_color_map(led.normalized_distance()));
+    }
+
+    /// Maps LEDs to a color depending on their [normalized angle](Led::normalized_angle) from the `center_point`.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// sled.map_by_normalized_angle(|turns| Rgb::new(turns, turns, turns));
+    /// ```
+    pub fn map_by_normalized_angle(&mut self, angle_to_color_map: impl Fn(f32) -> Color) {
+        self.update_all(|led| led.color = angle_to_color_map(led.normalized_angle()));
+    }
+
+    /// Maps LEDs to a color depending on the direction the strip runs in at each LED. See [Led::tangent()].
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// // brighten the strips that run horizontally
+    /// sled.map_by_tangent(|tangent| {
+    ///     let horizontal = tangent.x.abs();
+    ///     Rgb::new(horizontal, horizontal, horizontal)
+    /// });
+    /// ```
+    pub fn map_by_tangent(&mut self, tangent_to_color_map: impl Fn(Vec2) -> Color) {
+        self.update_all(|led| led.color = tangent_to_color_map(led.tangent()));
+    }
+
+    /// Maps LEDs to a color depending on the direction the strip faces at each LED. See [Led::normal()].
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let light_dir = Vec2::new(0.0, -1.0);
+    /// sled.map_by_normal(|normal| {
+    ///     let shade = normal.dot(light_dir).max(0.0);
+    ///     Rgb::new(shade, shade, shade)
+    /// });
+    /// ```
+    pub fn map_by_normal(&mut self, normal_to_color_map: impl Fn(Vec2) -> Color) {
+        self.update_all(|led| led.color = normal_to_color_map(led.normal()));
+    }
+
+    /// Maps LEDs to a color depending on how far along the whole strip they are. See [Led::path_pos()].
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let length = sled.path_length();
+    /// sled.map_by_path_pos(|path_pos| {
+    ///     let alpha = path_pos / length;
+    ///     Rgb::new(alpha, 0.0, 1.0 - alpha)
+    /// });
+    /// ```
+    pub fn map_by_path_pos(&mut self, path_pos_to_color_map: impl Fn(f32) -> Color) {
+        self.update_all(|led| led.color = path_pos_to_color_map(led.path_pos()));
+    }
+
+    /// Maps LEDs to a color depending on how far along their chain they are. See [Led::chain_pos()].
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// sled.map_by_chain_pos(|chain_pos| {
+    ///     let stripe = (chain_pos * 2.0).fract();
+    ///     Rgb::new(stripe, stripe, stripe)
+    /// });
+    /// ```
+    pub fn map_by_chain_pos(&mut self, chain_pos_to_color_map: impl Fn(f32) -> Color) {
+        self.update_all(|led| led.color = chain_pos_to_color_map(led.chain_pos()));
+    }
 }
 
 /// Filters
@@ -216,12 +325,13 @@ impl<Color: ColorType> Sled<Color> {
     /// sled.set_filter(&odd, Rgb::new(1.0, 1.0, 1.0));
     /// ```
     pub fn filter(&self, filter: impl Fn(&Led<Color>) -> bool) -> Filter {
-        let filtered: BTreeSet<u16> = self
-            .leds
-            .iter()
-            .filter_map(|led| if filter(led) { Some(led.index()) } else { None })
-            .collect();
-        filtered.into()
+        let mut filtered = Filter::with_capacity(self.num_leds());
+        for led in self.leds() {
+            if filter(&led) {
+                filtered.insert(led.index());
+            }
+        }
+        filtered
     }
 
     /// Returns a [Filter] containing all LEDs whose angle meets a certain criteria.
@@ -294,4 +404,73 @@ impl<Color: ColorType> Sled<Color> {
             dist_filter(dist)
         })
     }
+
+    /// Returns a [Filter] containing all LEDs whose [uv coordinates](Led::uv) meet a certain criteria.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let left_third = sled.filter_by_uv(|uv| uv.x < 1.0 / 3.0);
+    /// sled.set_filter(&left_third, Rgb::new(1.0, 1.0, 1.0));
+    /// ```
+    pub fn filter_by_uv(&self, uv_filter: impl Fn(Vec2) -> bool) -> Filter {
+        self.filter(|led| uv_filter(led.uv()))
+    }
+
+    /// Returns a [Filter] containing all LEDs whose [tangent](Led::tangent) meets a certain criteria.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let vertical = sled.filter_by_tangent(|tangent| tangent.y.abs() > 0.9);
+    /// sled.set_filter(&vertical, Rgb::new(1.0, 1.0, 1.0));
+    /// ```
+    pub fn filter_by_tangent(&self, tangent_filter: impl Fn(Vec2) -> bool) -> Filter {
+        self.filter(|led| tangent_filter(led.tangent()))
+    }
+
+    /// Returns a [Filter] containing all LEDs whose [normal](Led::normal) meets a certain criteria.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let wind = Vec2::new(1.0, 0.0);
+    /// // light the strips that face into the wind
+    /// let windward = sled.filter_by_normal(|normal| normal.dot(wind) < 0.0);
+    /// sled.set_filter(&windward, Rgb::new(1.0, 1.0, 1.0));
+    /// ```
+    pub fn filter_by_normal(&self, normal_filter: impl Fn(Vec2) -> bool) -> Filter {
+        self.filter(|led| normal_filter(led.normal()))
+    }
+
+    /// Returns a [Filter] containing all LEDs whose [path position](Led::path_pos) meets a certain criteria.
+    ///
+    /// For contiguous intervals, [Sled::path_interval()] is faster.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let dashes = sled.filter_by_path_pos(|path_pos| path_pos % 1.0 < 0.5);
+    /// sled.set_filter(&dashes, Rgb::new(1.0, 1.0, 1.0));
+    /// ```
+    pub fn filter_by_path_pos(&self, path_pos_filter: impl Fn(f32) -> bool) -> Filter {
+        self.filter(|led| path_pos_filter(led.path_pos()))
+    }
+
+    /// Returns a [Filter] containing all LEDs whose [chain position](Led::chain_pos) meets a certain criteria.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Filter, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let chain_starts = sled.filter_by_chain_pos(|chain_pos| chain_pos < 1.0);
+    /// sled.set_filter(&chain_starts, Rgb::new(1.0, 1.0, 1.0));
+    /// ```
+    pub fn filter_by_chain_pos(&self, chain_pos_filter: impl Fn(f32) -> bool) -> Filter {
+        self.filter(|led| chain_pos_filter(led.chain_pos()))
+    }
 }
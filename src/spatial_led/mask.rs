@@ -0,0 +1,305 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::TAU;
+use core::ops::{Add, Mul};
+
+#[cfg(not(feature = "std"))]
+use num_traits::float::Float as _;
+
+use crate::{
+    color::ColorType,
+    led::Led,
+    spatial_led::{Filter, PathField, Sled},
+    Vec2,
+};
+
+#[derive(Clone, Debug, PartialEq, Default)]
+/// A soft counterpart to [Filter]. Rather than each [LED](Led) being strictly in or out, every LED carries a weight between 0 and 1.
+///
+/// Masks are handy for feathered edges: build one from a distance falloff, an angular sector or a [PathField], combine them with [Mask::min()], [Mask::max()] and [Mask::multiply()], then apply a color rule with [Sled::apply_mask()].
+///
+/// Like Filters, Masks can be computed at startup and saved to [Data](crate::driver::Data) if the shapes they describe don't move.
+///
+/// ```rust
+/// # use spatial_led::{Sled, Mask, Vec2};
+/// # use palette::rgb::Rgb;
+/// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+/// // a soft-edged spotlight, only on the upper half of the room
+/// let spot: Mask = sled.mask_by_dist_from(Vec2::new(0.5, 1.0), 1.0, 1.5);
+/// let upper: Mask = sled.mask_sector(0.0, 3.14, 0.2);
+/// sled.apply_mask(&spot.multiply(&upper), |_| Rgb::new(1.0, 0.9, 0.7));
+/// ```
+pub struct Mask {
+    weights: Vec<f32>,
+}
+
+impl Mask {
+    /// Returns the weight of the [LED](Led) at `index`. LEDs outside the Mask have a weight of 0.
+    pub fn get(&self, index: usize) -> f32 {
+        self.weights.get(index).copied().unwrap_or(0.0)
+    }
+
+    /// Returns an Iterator over the weight of each [LED](Led), in index order.
+    pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
+        self.weights.iter().copied()
+    }
+
+    /// Returns a [Filter] containing every [LED](Led) with a weight above zero.
+    pub fn support(&self) -> Filter {
+        self.to_filter(0.0)
+    }
+
+    /// Returns a [Filter] containing every [LED](Led) whose weight is greater than `threshold`.
+    pub fn to_filter(&self, threshold: f32) -> Filter {
+        self.weights
+            .iter()
+            .enumerate()
+            .filter_map(|(i, w)| (*w > threshold).then_some(i as u16))
+            .collect()
+    }
+
+    /// Returns a new Mask with `weight_map` applied to each weight. The results are clamped between 0 and 1.
+    ///
+    /// Useful for reshaping a linear falloff, for example with a smoothstep.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Mask};
+    /// # use palette::rgb::Rgb;
+    /// # let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let linear: Mask = sled.mask_by_dist(1.0, 3.0);
+    /// let smooth = linear.map(|w| w * w * (3.0 - 2.0 * w));
+    /// ```
+    pub fn map(&self, weight_map: impl Fn(f32) -> f32) -> Mask {
+        Mask {
+            weights: self
+                .weights
+                .iter()
+                .map(|w| weight_map(*w).clamp(0.0, 1.0))
+                .collect(),
+        }
+    }
+
+    /// Returns a new Mask where each weight has been multiplied by `factor`, clamped between 0 and 1.
+    pub fn scale(&self, factor: f32) -> Mask {
+        self.map(|w| w * factor)
+    }
+
+    fn combine(&self, other: &Mask, op: impl Fn(f32, f32) -> f32) -> Mask {
+        let len = self.weights.len().max(other.weights.len());
+        Mask {
+            weights: (0..len).map(|i| op(self.get(i), other.get(i))).collect(),
+        }
+    }
+
+    /// Returns a new Mask holding the lesser of the two weights for each [LED](Led). Acts like a soft [Filter::and()].
+    pub fn min(&self, other: &Mask) -> Mask {
+        self.combine(other, f32::min)
+    }
+
+    /// Returns a new Mask holding the greater of the two weights for each [LED](Led). Acts like a soft [Filter::or()].
+    pub fn max(&self, other: &Mask) -> Mask {
+        self.combine(other, f32::max)
+    }
+
+    /// Returns a new Mask holding the product of the two weights for each [LED](Led).
+    pub fn multiply(&self, other: &Mask) -> Mask {
+        self.combine(other, |a, b| a * b)
+    }
+}
+
+/// 1 at or below `inner`, 0 at or beyond `outer`, linear in between.
+fn falloff(value: f32, inner: f32, outer: f32) -> f32 {
+    if value <= inner {
+        1.0
+    } else if value >= outer {
+        0.0
+    } else {
+        (outer - value) / (outer - inner)
+    }
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    let wrapped = angle % TAU;
+    if wrapped < 0.0 {
+        wrapped + TAU
+    } else {
+        wrapped
+    }
+}
+
+/// # Weighted mask methods
+impl<Color: ColorType> Sled<Color> {
+    /// Returns a [Mask] holding the weight `weight_rule` assigns to each [LED](Led), clamped between 0 and 1.
+    ///
+    /// O(LEDS)
+    pub fn mask(&self, weight_rule: impl Fn(&Led<Color>) -> f32) -> Mask {
+        Mask {
+            weights: self
+                .leds()
+                .map(|led| weight_rule(&led).clamp(0.0, 1.0))
+                .collect(),
+        }
+    }
+
+    /// Returns a [Mask] that is 1 for every [LED](Led) in `filter` and 0 for the rest.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled};
+    /// # use palette::rgb::Rgb;
+    /// # let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let first_two = sled.filter(|led| led.index() < 2);
+    /// let rest = sled.mask_from_filter(&first_two).map(|w| 1.0 - w);
+    /// assert_eq!(rest.get(0), 0.0);
+    /// assert_eq!(rest.get(sled.num_leds() - 1), 1.0);
+    /// assert_eq!(rest.weights().count(), sled.num_leds());
+    /// ```
+    pub fn mask_from_filter(&self, filter: &Filter) -> Mask {
+        let mut weights = vec![0.0; self.num_leds()];
+        for i in filter {
+            if let Some(weight) = weights.get_mut(i as usize) {
+                *weight = 1.0;
+            }
+        }
+        Mask { weights }
+    }
+
+    /// Returns a [Mask] that is 1 for [LEDs](Led) within `inner` of the center point, fading linearly to 0 at `outer`.
+    ///
+    /// O(LEDS)
+    pub fn mask_by_dist(&self, inner: f32, outer: f32) -> Mask {
+        self.mask(|led| falloff(led.distance(), inner, outer))
+    }
+
+    /// Returns a [Mask] that is 1 for [LEDs](Led) within `inner` of `pos`, fading linearly to 0 at `outer`.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// // a ripple ring with feathered edges on both sides
+    /// let pos = Vec2::new(1.0, 0.5);
+    /// let radius = 2.0;
+    /// let inside = sled.mask_by_dist_from(pos, radius, radius + 0.15);
+    /// let hole = sled.mask_by_dist_from(pos, radius - 0.3, radius - 0.15);
+    /// let ring = inside.multiply(&hole.map(|w| 1.0 - w));
+    /// sled.apply_mask(&ring, |_| Rgb::new(0.0, 0.5, 1.0));
+    /// ```
+    pub fn mask_by_dist_from(&self, pos: Vec2, inner: f32, outer: f32) -> Mask {
+        self.mask(|led| falloff(led.position().distance(pos), inner, outer))
+    }
+
+    /// Returns a [Mask] that is 1 for [LEDs](Led) whose angle from the center point lies within the sector running counterclockwise from `start_angle` to `end_angle`, fading linearly to 0 over `feather` radians beyond either edge.
+    ///
+    /// O(LEDS)
+    ///
+    /// A sector spanning a full turn or more covers every LED.
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled};
+    /// # use palette::rgb::Rgb;
+    /// # let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let everywhere = sled.mask_sector(0.0, std::f32::consts::TAU, 0.0);
+    /// assert!(everywhere.weights().all(|w| w == 1.0));
+    /// ```
+    pub fn mask_sector(&self, start_angle: f32, end_angle: f32, feather: f32) -> Mask {
+        self.mask_sector_from(self.layout.center_point, start_angle, end_angle, feather)
+    }
+
+    /// Returns a [Mask] that is 1 for [LEDs](Led) whose angle from `pos` lies within the sector running counterclockwise from `start_angle` to `end_angle`, fading linearly to 0 over `feather` radians beyond either edge.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled, Vec2};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// // a soft-edged radar sweep
+    /// let heading = 1.2;
+    /// let beam = sled.mask_sector_from(Vec2::new(0.0, 0.5), heading - 0.3, heading, 0.5);
+    /// sled.apply_mask(&beam, |_| Rgb::new(0.0, 1.0, 0.0));
+    /// ```
+    pub fn mask_sector_from(
+        &self,
+        pos: Vec2,
+        start_angle: f32,
+        end_angle: f32,
+        feather: f32,
+    ) -> Mask {
+        let span = end_angle - start_angle;
+        // a full turn or more covers every angle, but would wrap around to a width of 0
+        let width = if span >= TAU { TAU } else { wrap_angle(span) };
+        self.mask(|led| {
+            let delta = led.position() - pos;
+            let angle = delta.y.atan2(delta.x);
+            let into_sector = wrap_angle(angle - start_angle);
+            if into_sector <= width {
+                return 1.0;
+            }
+
+            let outside = (into_sector - width).min(TAU - into_sector);
+            falloff(outside, 0.0, feather)
+        })
+    }
+
+    /// Returns a [Mask] that is 1 for [LEDs](Led) whose distance in the given [PathField] is within `inner`, fading linearly to 0 at `outer`.
+    ///
+    /// O(LEDS)
+    pub fn mask_by_path_dist(&self, field: &PathField, inner: f32, outer: f32) -> Mask {
+        Mask {
+            weights: field
+                .distances()
+                .map(|dist| falloff(dist, inner, outer))
+                .collect(),
+        }
+    }
+
+    /// Modulates the color of each [LED](Led) with a weight above zero in the given [Mask]. The color rule is passed the LED and its weight.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    /// # use spatial_led::{Sled};
+    /// # use palette::rgb::Rgb;
+    /// # let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let glow = sled.mask_by_dist(0.5, 2.5);
+    /// sled.modulate_mask(&glow, |led, weight| led.color + Rgb::new(weight, 0.0, 0.0));
+    /// ```
+    pub fn modulate_mask<F: Fn(&Led<Color>, f32) -> Color>(&mut self, mask: &Mask, color_rule: F) {
+        self.update_all(|led| {
+            let weight = mask.get(led.index() as usize);
+            if weight > 0.0 {
+                led.color = color_rule(led, weight);
+            }
+        });
+    }
+}
+
+impl<Color> Sled<Color>
+where
+    Color: ColorType + Add<Output = Color> + Mul<f32, Output = Color>,
+{
+    /// Blends each [LED](Led) toward the color given by `color_rule`, by an amount equal to its weight in the [Mask].
+    ///
+    /// A weight of 1 replaces the LED's color entirely, 0 leaves it untouched.
+    ///
+    /// O(LEDS)
+    pub fn apply_mask<F: Fn(&Led<Color>) -> Color>(&mut self, mask: &Mask, color_rule: F) {
+        self.update_all(|led| {
+            let weight = mask.get(led.index() as usize);
+            if weight > 0.0 {
+                led.color = led.color * (1.0 - weight) + color_rule(led) * weight;
+            }
+        });
+    }
+
+    /// Blends each [LED](Led) toward `color`, by an amount equal to its weight in the [Mask].
+    ///
+    /// O(LEDS)
+    pub fn set_mask(&mut self, mask: &Mask, color: Color) {
+        self.apply_mask(mask, |_| color);
+    }
+}
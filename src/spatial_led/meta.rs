@@ -1,17 +1,18 @@
 use core::ops::Range;
 
+use alloc::sync::Arc;
 use alloc::vec;
-use alloc::vec::Vec;
 
 #[cfg(not(feature = "std"))]
 use num_traits::float::Float as _;
 
 use crate::{
     color::ColorType,
-    config::{Config, LineSegment},
+    config::LineSegment,
     error::SledError,
     led::Led,
-    spatial_led::Sled,
+    rect::Rect,
+    spatial_led::{History, Layout, Sled, Topology},
     Vec2,
 };
 
@@ -46,54 +47,49 @@ impl<COLOR: ColorType> Sled<COLOR> {
     ///     ```
     #[cfg(feature = "std")]
     pub fn new(config_file_path: &str) -> Result<Self, SledError> {
-        let config = Config::from_toml_file(config_file_path)?;
-        Sled::new_from_config(config)
+        Ok(Sled::from_layout(Arc::new(Layout::new(config_file_path)?)))
     }
 
     /// Works like [Sled::new()] but rather than reading the contents of a config file from disk, allows you to pass in the same information as a `&str`.
     pub fn new_from_str(string: &str) -> Result<Self, SledError> {
-        let config = Config::from_str(string)?;
-        Sled::new_from_config(config)
+        Ok(Sled::from_layout(Arc::new(Layout::new_from_str(string)?)))
     }
 
-    fn new_from_config(config: Config) -> Result<Self, SledError> {
-        let leds_per_segment = Sled::<COLOR>::leds_per_segment(&config);
-        let leds = Sled::build_led_list(
-            &leds_per_segment,
-            &config.line_segments,
-            &config.center_point,
-        );
-        let line_segment_endpoint_indices =
-            Sled::<COLOR>::line_segment_endpoint_indices(&leds_per_segment);
-        let vertex_indices = Sled::<COLOR>::vertex_indices(&config);
-        let num_leds = leds.len();
-        let index_of_closest = leds
-            .iter()
-            .min_by(|l, r| l.distance().partial_cmp(&r.distance()).unwrap())
-            .unwrap()
-            .index() as usize;
+    /// Constructs a new Sled with every LED set to `COLOR::default()`, sharing an existing [Layout].
+    ///
+    /// This is much cheaper than [Sled::new()], since no geometry needs to be calculated or copied.
+    ///
+    /// O(LEDS)
+    pub fn from_layout(layout: Arc<Layout>) -> Self {
+        let colors = vec![COLOR::default(); layout.num_leds];
+        Sled {
+            layout,
+            colors,
+            history: History::default(),
+        }
+    }
 
-        let index_of_furthest = leds
-            .iter()
-            .max_by(|l, r| l.distance().partial_cmp(&r.distance()).unwrap())
-            .unwrap()
-            .index() as usize;
-
-        let domain = Sled::calc_domain(&leds);
-
-        Ok(Sled {
-            center_point: config.center_point,
-            leds,
-            num_leds,
-            density: config.density,
-            line_segments: config.line_segments,
-            index_of_closest,
-            index_of_furthest,
-            domain,
-            // utility lookup tables
-            line_segment_endpoint_indices,
-            vertex_indices,
-        })
+    /// Returns the shared [Layout] describing this Sled's geometry.
+    ///
+    /// O(1)
+    pub fn layout(&self) -> &Arc<Layout> {
+        &self.layout
+    }
+
+    /// Returns the Sled's [Layout] mutably, so that properties like its [calibration](Layout::set_calibration) can be changed.
+    ///
+    /// If the Layout is shared with other Sleds, this Sled first gets its own copy, leaving the others untouched. Make any changes before cloning the Sled if they should apply to every copy.
+    ///
+    /// O(1), or O(LEDS) if the Layout has to be copied.
+    pub fn layout_mut(&mut self) -> &mut Layout {
+        Arc::make_mut(&mut self.layout)
+    }
+
+    /// Returns true if this Sled and `other` share the same [Layout] in memory.
+    ///
+    /// O(1)
+    pub fn shares_layout_with<C: ColorType>(&self, other: &Sled<C>) -> bool {
+        Arc::ptr_eq(&self.layout, &other.layout)
     }
 
     /// Returns a read-only iterator over the system's [LEDs](Led).
@@ -112,8 +108,11 @@ impl<COLOR: ColorType> Sled<COLOR> {
     ///     );
     /// }
     /// ```
-    pub fn leds(&self) -> impl Iterator<Item = &Led<COLOR>> {
-        self.leds.iter()
+    pub fn leds(&self) -> impl Iterator<Item = Led<'_, COLOR>> {
+        self.colors
+            .iter()
+            .zip(&self.layout.leds)
+            .map(|(color, geometry)| Led::new(*color, geometry))
     }
 
     /// Returns an Iterator over the 32-bit RGB colors for each [LED](Led) in the system
@@ -132,42 +131,45 @@ impl<COLOR: ColorType> Sled<COLOR> {
     /// }
     /// ```
     pub fn colors(&self) -> impl Iterator<Item = &COLOR> + '_ {
-        self.leds.iter().map(|led| &led.color)
+        self.colors.iter()
     }
 
     /// Returns an Iterator over Vec2s, representing the position of each [LED](Led) in the system.
     ///
     /// O(LEDS)
     pub fn positions(&self) -> impl Iterator<Item = Vec2> + '_ {
-        self.leds.iter().map(|led| led.position())
+        self.layout.leds.iter().map(|led| led.position())
     }
 
     /// Returns an Iterator over tuple pairs of the color and position of each [LED](Led) in the system.
     ///
     /// O(LEDS)
     pub fn colors_and_positions(&self) -> impl Iterator<Item = (COLOR, Vec2)> + '_ {
-        self.leds.iter().map(|led| (led.color, led.position()))
+        self.colors
+            .iter()
+            .zip(&self.layout.leds)
+            .map(|(color, led)| (*color, led.position()))
     }
 
     /// Returns the static reference point declared in the [config file](Sled::new).
     ///
     /// O(1)
     pub fn center_point(&self) -> Vec2 {
-        self.center_point
+        self.layout.center_point
     }
 
     /// Returns the total number of [LEDs](Led) in the system.
     ///
     /// O(1)
     pub fn num_leds(&self) -> usize {
-        self.num_leds
+        self.layout.num_leds
     }
 
     /// Returns the total number of line segments in the system.
     ///
     /// O(1)
     pub fn num_segments(&self) -> usize {
-        self.line_segments.len()
+        self.layout.line_segments.len()
     }
 
     /// Returns the total number of vertices in the system.
@@ -178,115 +180,105 @@ impl<COLOR: ColorType> Sled<COLOR> {
     ///
     /// O(1)
     pub fn num_vertices(&self) -> usize {
-        self.vertex_indices.len()
+        self.layout.vertex_indices.len()
     }
 
-    /// Returns a bounding box around the LEDs where the minimum x and y
-    /// position is [Range::start], maximum x and y is [Range::end].
+    /// Returns the total number of chains in the system.
+    ///
+    /// A chain is a run of line segments connected by `-->` in the [config file](Sled::new).
+    /// Each `|` separator starts a new chain.
     ///
     /// O(1)
-    pub fn domain(&self) -> Range<Vec2> {
-        self.domain.clone()
+    pub fn num_chains(&self) -> usize {
+        self.layout.chain_lengths.len()
     }
 
-    fn leds_per_segment(config: &Config) -> Vec<usize> {
-        config
-            .line_segments
-            .iter()
-            .map(|line| line.num_leds(config.density))
-            .collect()
+    /// Returns the combined length of every line segment in the chain with index `chain_index`, if it exists.
+    ///
+    /// O(1)
+    pub fn chain_length(&self, chain_index: usize) -> Option<f32> {
+        self.layout.chain_lengths.get(chain_index).copied()
     }
 
-    fn build_led_list(
-        leds_per_segment: &[usize],
-        line_segments: &[LineSegment],
-        center_point: &Vec2,
-    ) -> Vec<Led<COLOR>> {
-        let mut leds = vec![];
-        let default_color = COLOR::default();
-
-        for (segment_index, segment_size) in leds_per_segment.iter().enumerate() {
-            for i in 0..*segment_size {
-                let segment = &line_segments[segment_index];
-                let alpha = (i + 1) as f32 / *segment_size as f32;
-                let pos = segment.start.lerp(segment.end, alpha);
-                let led = Led::new(
-                    default_color,
-                    pos,
-                    leds.len() as u16,
-                    segment_index as u8,
-                    *center_point,
-                );
-
-                leds.push(led);
-            }
-        }
-        leds
+    /// Returns the combined length of every line segment in the system.
+    ///
+    /// O(1)
+    pub fn path_length(&self) -> f32 {
+        self.layout.path_length
     }
 
-    fn line_segment_endpoint_indices(leds_per_segment: &Vec<usize>) -> Vec<(usize, usize)> {
-        let mut line_segment_endpoint_indices = vec![];
-        let mut last_index = 0;
-        for num_leds in leds_per_segment {
-            line_segment_endpoint_indices.push((last_index, last_index + num_leds));
-            last_index += num_leds;
-        }
-
-        line_segment_endpoint_indices
+    /// Returns a bounding box around the LEDs.
+    ///
+    /// O(1)
+    pub fn domain(&self) -> Rect {
+        self.layout.domain
     }
 
-    fn vertex_indices(config: &Config) -> Vec<usize> {
-        let mut vertex_indices = vec![];
+    pub(crate) fn topology(&self) -> &Topology {
+        &self.layout.topology
+    }
 
-        let start = config.line_segments[0].start;
-        let end = config.line_segments[config.line_segments.len() - 1].end;
+    pub(crate) fn line_segment(&self, segment_index: usize) -> &LineSegment {
+        &self.layout.line_segments[segment_index]
+    }
 
-        if start != end {
-            vertex_indices.push(0);
-        }
+    pub(crate) fn segment_led_range(&self, segment_index: usize) -> Range<usize> {
+        let (start, end) = self.layout.line_segment_endpoint_indices[segment_index];
+        start..end
+    }
 
-        let mut last_end_point: Vec2 = start;
-        let mut last_index = 0;
-        for line in &config.line_segments {
-            if line.start != last_end_point {
-                vertex_indices.push(last_index);
-            }
+    /// Returns a view of the LED at `index`. Panics if it doesn't exist.
+    pub(crate) fn led(&self, index: usize) -> Led<'_, COLOR> {
+        Led::new(self.colors[index], &self.layout.leds[index])
+    }
 
-            let num_leds = line.num_leds(config.density);
-            vertex_indices.push(last_index + num_leds - 1);
+    /// Grants mutable access to the LED at `index`, writing back any change to its color. Panics if it doesn't exist.
+    pub(crate) fn update_led<F: FnOnce(&mut Led<COLOR>)>(&mut self, index: usize, func: F) {
+        let mut led = Led::new(self.colors[index], &self.layout.leds[index]);
+        func(&mut led);
+        self.colors[index] = led.color;
+    }
 
-            last_index += num_leds;
-            last_end_point = line.end;
+    /// Grants mutable access to each LED in `indices`, writing back any changes to their colors.
+    pub(crate) fn update_leds<F: FnMut(&mut Led<COLOR>)>(
+        &mut self,
+        indices: impl IntoIterator<Item = usize>,
+        mut func: F,
+    ) {
+        for index in indices {
+            let mut led = Led::new(self.colors[index], &self.layout.leds[index]);
+            func(&mut led);
+            self.colors[index] = led.color;
         }
-
-        vertex_indices
     }
 
-    fn calc_domain(leds: &Vec<Led<COLOR>>) -> Range<Vec2> {
-        let mut min_x = f32::MAX;
-        let mut min_y = f32::MAX;
-
-        let mut max_x = f32::MIN;
-        let mut max_y = f32::MIN;
-
-        for led in leds {
-            let p = led.position();
-
-            min_x = min_x.min(p.x);
-            min_y = min_y.min(p.y);
-
-            max_x = max_x.max(p.x);
-            max_y = max_y.max(p.y);
+    /// Grants mutable access to every LED in the system, writing back any changes to their colors.
+    pub(crate) fn update_all<F: FnMut(&mut Led<COLOR>)>(&mut self, mut func: F) {
+        for (color, geometry) in self.colors.iter_mut().zip(&self.layout.leds) {
+            let mut led = Led::new(*color, geometry);
+            func(&mut led);
+            *color = led.color;
         }
-
-        Vec2::new(min_x, min_y)..Vec2::new(max_x, max_y)
     }
 
     pub(crate) fn alpha_to_index(&self, segment_alpha: f32, segment_index: usize) -> usize {
-        let segment = &self.line_segments[segment_index];
-        let startpoint_index = self.line_segment_endpoint_indices[segment_index].0;
-        let leds_in_segment = segment.num_leds(self.density) as f32;
+        let segment = &self.layout.line_segments[segment_index];
+        let startpoint_index = self.layout.line_segment_endpoint_indices[segment_index].0;
+        let leds_in_segment = segment.num_leds(self.layout.density) as f32;
+
+        (startpoint_index + (segment_alpha * leds_in_segment).floor() as usize)
+            % self.layout.num_leds
+    }
+
+    /// Returns the index of the LED in the given segment closest to `segment_alpha`, never spilling over into a neighboring segment.
+    pub(crate) fn alpha_to_nearest_index(&self, segment_alpha: f32, segment_index: usize) -> usize {
+        let (start, end) = self.layout.line_segment_endpoint_indices[segment_index];
+        let leds_in_segment = end - start;
 
-        (startpoint_index + (segment_alpha * leds_in_segment).floor() as usize) % self.num_leds
+        // the i-th LED of a segment sits at alpha (i + 1) / n
+        let offset = (segment_alpha * leds_in_segment as f32 - 1.0)
+            .round()
+            .max(0.0) as usize;
+        start + offset.min(leds_in_segment.saturating_sub(1))
     }
 }
@@ -1,10 +1,10 @@
-use core::ops::Range;
-
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 
-use crate::{color::ColorType, config::LineSegment, led::Led, Vec2};
+use crate::{
+    color::ColorType, config::LineSegment, led::LedGeometry, output::Calibration, rect::Rect, Vec2,
+};
 
-#[allow(dead_code)]
 #[derive(Clone, Debug)]
 /// A struct representing the layout of some LED configuration in 2D space, composed of line segments.
 ///
@@ -30,34 +30,88 @@ use crate::{color::ColorType, config::LineSegment, led::Led, Vec2};
 ///     w: 0.0
 /// });
 /// ```
+///
+/// A Sled is made of two parts: an immutable, reference-counted [Layout] describing where every LED is, and a buffer holding one color per LED.
+/// Cloning a Sled only copies its colors and [frame history](Sled::set_history_len); the clone shares the original's Layout. See [Sled::from_layout()].
 pub struct Sled<COLOR: ColorType> {
+    layout: Arc<Layout>,
+    colors: Vec<COLOR>,
+    history: History<COLOR>,
+}
+
+#[derive(Clone, Debug)]
+/// The immutable geometry of an LED configuration: the position of each LED, plus the lookup tables [Sled] uses to answer spatial queries quickly.
+///
+/// A Layout is built once from a [config file](Sled::new) and can then be shared by any number of Sleds, each holding its own colors.
+/// This makes double buffering or drawing several effects into separate buffers cheap, since the geometry is never copied.
+///
+/// A Layout also carries the [color calibration](Layout::set_calibration) of each LED, applied by the [OutputEncoder](crate::output::OutputEncoder).
+///
+/// ```rust
+/// # use spatial_led::{Sled, Layout};
+/// # use palette::rgb::Rgb;
+/// use std::sync::Arc;
+///
+/// let layout = Arc::new(Layout::new("./benches/config.yap").unwrap());
+/// let front = Sled::<Rgb>::from_layout(layout.clone());
+/// let back = Sled::<Rgb>::from_layout(layout);
+/// assert!(front.shares_layout_with(&back));
+/// ```
+pub struct Layout {
     center_point: Vec2,
-    leds: Vec<Led<COLOR>>,
+    leds: Vec<LedGeometry>,
     num_leds: usize,
     density: f32,
     line_segments: Vec<LineSegment>,
+    chain_lengths: Vec<f32>,
+    path_length: f32,
     // utility lookup tables
     line_segment_endpoint_indices: Vec<(usize, usize)>,
+    chain_endpoint_indices: Vec<(usize, usize)>,
     vertex_indices: Vec<usize>,
+    topology: Topology,
     index_of_closest: usize,
     index_of_furthest: usize,
-    domain: Range<Vec2>,
+    domain: Rect,
+    calibrations: Vec<Calibration>,
 }
 
 // goofy spacing to preserve order after auto-formatting.
 // Mostly just important for docs.
 
+mod layout;
+
 mod meta;
 
 mod indexical;
 
 mod segmental;
 
+mod chains;
+
+mod topology;
+pub use topology::Junction;
+pub(crate) use topology::Topology;
+
+mod geodesic;
+pub use geodesic::PathField;
+
+mod blur;
+pub use blur::{BlurKernel, KernelShape, Neighborhood};
+
 mod directional;
+pub use directional::RayHit;
 
 mod positional;
 
 mod maps_and_filters;
 
+mod history;
+use history::History;
+
 mod filter;
 pub use filter::Filter;
+
+mod mask;
+pub use filter::{IntoIter as FilterIntoIter, Iter as FilterIter};
+pub use mask::Mask;
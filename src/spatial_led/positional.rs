@@ -1,4 +1,4 @@
-use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
 
 use crate::{
     color::ColorType,
@@ -24,6 +24,7 @@ impl<Color: ColorType> Sled<Color> {
         // get the closest point on each segment and bundle relevant info,
         // then find the closest of those points
         let (alpha, _dist_sq, segment_index) = self
+            .layout
             .line_segments
             .iter()
             .enumerate()
@@ -41,16 +42,16 @@ impl<Color: ColorType> Sled<Color> {
     /// Returns the [LED](Led) closest to the center point.
     ///
     /// O(1)
-    pub fn closest(&self) -> &Led<Color> {
-        &self.leds[self.index_of_closest]
+    pub fn closest(&self) -> Led<'_, Color> {
+        self.led(self.layout.index_of_closest)
     }
 
     /// Returns the [LED](Led) closest to a given point.
     ///
     /// O(SEGMENTS)
-    pub fn closest_to(&self, pos: Vec2) -> &Led<Color> {
+    pub fn closest_to(&self, pos: Vec2) -> Led<'_, Color> {
         let index_of_closest = self.index_of_closest_to(pos);
-        &self.leds[index_of_closest]
+        self.led(index_of_closest)
     }
 
     /// Modulates the color of the [LED](Led) closest to the center point.
@@ -66,8 +67,9 @@ impl<Color: ColorType> Sled<Color> {
     ///# Ok(())
     ///# }
     pub fn modulate_closest<F: Fn(&Led<Color>) -> Color>(&mut self, color_rule: F) {
-        let led = &mut self.leds[self.index_of_closest];
-        led.color = color_rule(led);
+        self.update_led(self.layout.index_of_closest, |led| {
+            led.color = color_rule(led)
+        });
     }
 
     /// Modulates the color of the [LED](Led) closest to a given point.
@@ -86,15 +88,14 @@ impl<Color: ColorType> Sled<Color> {
     ///# }
     pub fn modulate_closest_to<F: Fn(&Led<Color>) -> Color>(&mut self, pos: Vec2, color_rule: F) {
         let index_of_closest = self.index_of_closest_to(pos);
-        let led = &mut self.leds[index_of_closest];
-        led.color = color_rule(led);
+        self.update_led(index_of_closest, |led| led.color = color_rule(led));
     }
 
     /// Sets the color of the [LED](Led) closest to the center point.
     ///
     /// O(1)
     pub fn set_closest(&mut self, color: Color) {
-        self.leds[self.index_of_closest].color = color;
+        self.colors[self.layout.index_of_closest] = color;
     }
 
     /// Sets the color of the [LED](Led) closest to a given point.
@@ -102,7 +103,7 @@ impl<Color: ColorType> Sled<Color> {
     /// O(SEGMENTS)
     pub fn set_closest_to(&mut self, pos: Vec2, color: Color) {
         let index_of_closest = self.index_of_closest_to(pos);
-        self.leds[index_of_closest].color = color;
+        self.colors[index_of_closest] = color;
     }
 
     /* furthest getters/setters */
@@ -113,10 +114,11 @@ impl<Color: ColorType> Sled<Color> {
     pub fn index_of_furthest_from(&self, pos: Vec2) -> usize {
         // get the distance_squared of each vertex point, then find out which is the furthest.
         let (index_of_furthest, _dist) = self
+            .layout
             .vertex_indices
             .iter()
             .map(|i| {
-                let vertex_pos = self.leds[*i].position();
+                let vertex_pos = self.layout.leds[*i].position();
                 (*i, pos.distance_squared(vertex_pos))
             })
             .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
@@ -129,21 +131,21 @@ impl<Color: ColorType> Sled<Color> {
     ///
     /// O(1)
     pub fn index_of_furthest(&self) -> usize {
-        self.index_of_furthest
+        self.layout.index_of_furthest
     }
     /// Returns the [LED](Led) furthest from the center point.
     ///
     /// O(1)
-    pub fn furthest(&self) -> &Led<Color> {
-        &self.leds[self.index_of_furthest]
+    pub fn furthest(&self) -> Led<'_, Color> {
+        self.led(self.layout.index_of_furthest)
     }
 
     /// Returns the [LED](Led) furthest from a given point.
     ///
     /// O(VERTICES)
-    pub fn furthest_from(&self, pos: Vec2) -> &Led<Color> {
+    pub fn furthest_from(&self, pos: Vec2) -> Led<'_, Color> {
         let index_of_furthest = self.index_of_furthest_from(pos);
-        &self.leds[index_of_furthest]
+        self.led(index_of_furthest)
     }
 
     /// Modulates the color of the [LED](Led) furthest from the center point.
@@ -158,8 +160,9 @@ impl<Color: ColorType> Sled<Color> {
     ///# Ok(())
     ///# }
     pub fn modulate_furthest<F: Fn(&Led<Color>) -> Color>(&mut self, color_rule: F) {
-        let led = &mut self.leds[self.index_of_furthest];
-        led.color = color_rule(led);
+        self.update_led(self.layout.index_of_furthest, |led| {
+            led.color = color_rule(led)
+        });
     }
 
     /// Modulates the color of the [LED](Led) furthest from a given point
@@ -182,15 +185,14 @@ impl<Color: ColorType> Sled<Color> {
         color_rule: F,
     ) {
         let index_of_furthest = self.index_of_furthest_from(pos);
-        let led = &mut self.leds[index_of_furthest];
-        led.color = color_rule(led);
+        self.update_led(index_of_furthest, |led| led.color = color_rule(led));
     }
 
     /// Sets the color of the [LED](Led) furthest from the center point.
     ///
     /// O(1)
     pub fn set_furthest(&mut self, color: Color) {
-        self.leds[self.index_of_furthest].color = color;
+        self.colors[self.layout.index_of_furthest] = color;
     }
 
     /// Sets the color of the [LED](Led) furthest from a given point.
@@ -198,14 +200,14 @@ impl<Color: ColorType> Sled<Color> {
     /// O(VERTICES)
     pub fn set_furthest_from(&mut self, pos: Vec2, color: Color) {
         let index_of_furthest = self.index_of_furthest_from(pos);
-        self.leds[index_of_furthest].color = color;
+        self.colors[index_of_furthest] = color;
     }
 
     /* at distance methods */
 
     fn indices_at_dist(&self, pos: Vec2, dist: f32) -> SmallVec<[usize; 8]> {
         let mut all_at_distance = smallvec![];
-        for (segment_index, segment) in self.line_segments.iter().enumerate() {
+        for (segment_index, segment) in self.layout.line_segments.iter().enumerate() {
             for alpha in segment.intersects_circle(pos, dist) {
                 let index = self.alpha_to_index(alpha, segment_index);
                 all_at_distance.push(index);
@@ -216,20 +218,20 @@ impl<Color: ColorType> Sled<Color> {
     }
 
     pub fn at_dist(&self, dist: f32) -> Filter {
-        self.at_dist_from(dist, self.center_point)
+        self.at_dist_from(dist, self.layout.center_point)
     }
 
     pub fn at_dist_from(&self, dist: f32, pos: Vec2) -> Filter {
-        let mut all_at_distance = BTreeSet::new();
+        let mut all_at_distance = Filter::with_capacity(self.num_leds());
 
-        for (segment_index, segment) in self.line_segments.iter().enumerate() {
+        for (segment_index, segment) in self.layout.line_segments.iter().enumerate() {
             for alpha in segment.intersects_circle(pos, dist) {
                 let index = self.alpha_to_index(alpha, segment_index);
                 all_at_distance.insert(index as u16);
             }
         }
 
-        all_at_distance.into()
+        all_at_distance
     }
 
     pub fn modulate_at_dist<F: Fn(&Led<Color>) -> Color>(
@@ -237,7 +239,7 @@ impl<Color: ColorType> Sled<Color> {
         dist: f32,
         color_rule: F,
     ) -> bool {
-        self.modulate_at_dist_from(dist, self.center_point, color_rule)
+        self.modulate_at_dist_from(dist, self.layout.center_point, color_rule)
     }
 
     pub fn modulate_at_dist_from<F: Fn(&Led<Color>) -> Color>(
@@ -249,15 +251,14 @@ impl<Color: ColorType> Sled<Color> {
         let indices = self.indices_at_dist(pos, dist);
         let anything_found = !indices.is_empty();
         for i in indices {
-            let led = &mut self.leds[i];
-            led.color = color_rule(led);
+            self.update_led(i, |led| led.color = color_rule(led));
         }
 
         anything_found
     }
 
     pub fn set_at_dist(&mut self, dist: f32, color: Color) -> bool {
-        self.set_at_dist_from(dist, self.center_point, color)
+        self.set_at_dist_from(dist, self.layout.center_point, color)
     }
 
     pub fn set_at_dist_from(&mut self, dist: f32, pos: Vec2, color: Color) -> bool {
@@ -265,7 +266,7 @@ impl<Color: ColorType> Sled<Color> {
         let anything_found = !indices.is_empty();
 
         for index in indices {
-            self.leds[index].color = color;
+            self.colors[index] = color;
         }
 
         anything_found
@@ -274,21 +275,21 @@ impl<Color: ColorType> Sled<Color> {
     /* within distance methods */
 
     pub fn within_dist(&self, dist: f32) -> Filter {
-        self.within_dist_from(dist, self.center_point)
+        self.within_dist_from(dist, self.layout.center_point)
     }
 
     pub fn within_dist_from(&self, dist: f32, pos: Vec2) -> Filter {
-        let mut all_within_distance = BTreeSet::new();
+        let mut all_within_distance = Filter::with_capacity(self.num_leds());
 
         let target_sq = dist.powi(2);
 
-        for led in &self.leds {
+        for led in &self.layout.leds {
             if led.position().distance_squared(pos) < target_sq {
                 all_within_distance.insert(led.index());
             }
         }
 
-        all_within_distance.into()
+        all_within_distance
     }
 
     pub fn modulate_within_dist<F: Fn(&Led<Color>) -> Color>(
@@ -298,12 +299,12 @@ impl<Color: ColorType> Sled<Color> {
     ) -> bool {
         let mut changes_made = false;
 
-        for led in &mut self.leds {
+        self.update_all(|led| {
             if led.distance() < dist {
                 led.color = color_rule(led);
                 changes_made = true;
             }
-        }
+        });
 
         changes_made
     }
@@ -311,12 +312,12 @@ impl<Color: ColorType> Sled<Color> {
     pub fn set_within_dist(&mut self, dist: f32, color: Color) -> bool {
         let mut changes_made = false;
 
-        for led in &mut self.leds {
+        self.update_all(|led| {
             if led.distance() < dist {
                 led.color = color;
                 changes_made = true;
             }
-        }
+        });
 
         changes_made
     }
@@ -330,12 +331,12 @@ impl<Color: ColorType> Sled<Color> {
         let target_sq = dist.powi(2);
         let mut changes_made = false;
 
-        for led in &mut self.leds {
+        self.update_all(|led| {
             if led.position().distance_squared(pos) < target_sq {
                 led.color = color_rule(led);
                 changes_made = true
             }
-        }
+        });
 
         changes_made
     }
@@ -344,13 +345,136 @@ impl<Color: ColorType> Sled<Color> {
         let target_sq = dist.powi(2);
         let mut changes_made = false;
 
-        for led in &mut self.leds {
+        self.update_all(|led| {
             if led.position().distance_squared(pos) < target_sq {
                 led.color = color;
                 changes_made = true;
             }
-        }
+        });
 
         changes_made
     }
+
+    /* swept methods */
+
+    /// Returns the index of each [LED](Led) within `dist` of the line segment running from `from` to `to`, paired with a "t" value describing where along the sweep it was passed. 0 = `from`, 1 = `to`.
+    ///
+    /// Results are ordered by t, so a fast-moving object can paint a continuous trail between frames instead of skipping LEDs.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Vec2};
+    ///# use palette::rgb::Rgb;
+    ///# let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let previous = Vec2::new(-2.0, 1.5);
+    /// let current = Vec2::new(3.0, 1.5);
+    /// let swept = sled.sweep(previous, current, 1.0);
+    /// assert!(swept.windows(2).all(|w| w[0].1 <= w[1].1));
+    /// assert!(swept.iter().all(|(_, t)| (0.0..=1.0).contains(t)));
+    ///
+    /// for (index, t) in swept {
+    ///     // older parts of the trail are dimmer
+    ///     sled.set(index, Rgb::new(t, t * 0.5, 0.0)).unwrap();
+    /// }
+    /// ```
+    ///
+    /// An object that crossed most of a strip in a single frame still touches every LED it passed:
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Vec2};
+    /// let sled = Sled::<f32>::new_from_str(
+    ///     "center: (0, 0)\ndensity: 10\n--segments--\n(0, 0) --> (1, 0)",
+    /// ).unwrap();
+    /// let swept = sled.sweep(Vec2::new(-1.0, 0.05), Vec2::new(0.45, 0.05), 0.1);
+    /// let indices: Vec<usize> = swept.iter().map(|(index, _)| *index).collect();
+    /// assert_eq!(indices, [0, 1, 2, 3, 4]);
+    /// assert_eq!(swept[4].1, 1.0);
+    /// ```
+    pub fn sweep(&self, from: Vec2, to: Vec2, dist: f32) -> Vec<(usize, f32)> {
+        let dir = to - from;
+        let inv_len_sq = if dir.length_squared() > 0.0 {
+            dir.length_squared().recip()
+        } else {
+            0.0
+        };
+        let target_sq = dist.powi(2);
+
+        let mut swept: Vec<(usize, f32)> = self
+            .layout
+            .leds
+            .iter()
+            .filter_map(|led| {
+                let t = ((led.position() - from).dot(dir) * inv_len_sq).clamp(0.0, 1.0);
+                let closest = from + dir * t;
+                (led.position().distance_squared(closest) < target_sq)
+                    .then_some((led.index() as usize, t))
+            })
+            .collect();
+
+        swept.sort_by(|a, b| a.1.total_cmp(&b.1));
+        swept
+    }
+
+    /// Modulates the color of each [LED](Led) within `dist` of the line segment running from `from` to `to`. See [Sled::sweep()].
+    ///
+    /// The color rule is also passed the LED's t value, with LEDs visited in the order they were swept.
+    ///
+    /// Returns true if any LEDs were modulated, false otherwise.
+    ///
+    /// O(LEDS)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Vec2};
+    ///# use palette::rgb::Rgb;
+    ///# let mut sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// let spark_then = Vec2::new(0.0, 0.0);
+    /// let spark_now = Vec2::new(4.0, -1.0);
+    /// sled.modulate_sweep(spark_then, spark_now, 0.5, |led, t| {
+    ///     led.color + Rgb::new(t, t, 0.0)
+    /// });
+    /// ```
+    pub fn modulate_sweep<F: Fn(&Led<Color>, f32) -> Color>(
+        &mut self,
+        from: Vec2,
+        to: Vec2,
+        dist: f32,
+        color_rule: F,
+    ) -> bool {
+        let swept = self.sweep(from, to, dist);
+        for (index, t) in &swept {
+            self.update_led(*index, |led| led.color = color_rule(led, *t));
+        }
+
+        !swept.is_empty()
+    }
+
+    /// Sets the color of each [LED](Led) within `dist` of the line segment running from `from` to `to`. See [Sled::sweep()].
+    ///
+    /// Returns true if any LEDs were set, false otherwise.
+    ///
+    /// O(LEDS)
+    pub fn set_sweep(&mut self, from: Vec2, to: Vec2, dist: f32, color: Color) -> bool {
+        let swept = self.sweep(from, to, dist);
+        for (index, _) in &swept {
+            self.colors[*index] = color;
+        }
+
+        !swept.is_empty()
+    }
+
+    /// For-each method granting mutable access to each [LED](Led) within `dist` of the line segment running from `from` to `to`, in the order they were swept. See [Sled::sweep()].
+    ///
+    /// O(LEDS)
+    pub fn for_each_in_sweep<F: FnMut(&mut Led<Color>, f32)>(
+        &mut self,
+        from: Vec2,
+        to: Vec2,
+        dist: f32,
+        mut func: F,
+    ) {
+        for (index, t) in self.sweep(from, to, dist) {
+            self.update_led(index, |led| func(led, t));
+        }
+    }
 }
@@ -1,13 +1,14 @@
 use core::ops::Range;
 
-use alloc::collections::BTreeSet;
 use alloc::format;
 use alloc::string::ToString;
+use alloc::sync::Arc;
 
 use crate::{
     color::ColorType,
     error::SledError,
     led::Led,
+    rect::Rect,
     spatial_led::{Filter, Sled},
 };
 
@@ -17,10 +18,29 @@ impl<Color: ColorType> Sled<Color> {
     ///
     /// O(LEDS_IN_SEGMENT)
     pub fn segment(&self, segment_index: usize) -> Option<Filter> {
-        let (start, end) = *self.line_segment_endpoint_indices.get(segment_index)?;
-        let led_range = &self.leds[start..end];
-        Some(led_range.into())
+        let (start, end) = *self
+            .layout
+            .line_segment_endpoint_indices
+            .get(segment_index)?;
+        Some(Filter::from_range(start..end))
     }
+    /// Returns a bounding box around the [LEDs](Led) assigned to the line segment with index `segment_index`.
+    ///
+    /// Returns None if the segment doesn't exist or holds no LEDs.
+    ///
+    /// O(LEDS_IN_SEGMENT)
+    pub fn segment_domain(&self, segment_index: usize) -> Option<Rect> {
+        let (start, end) = *self
+            .layout
+            .line_segment_endpoint_indices
+            .get(segment_index)?;
+        Rect::from_points(
+            self.layout.leds[start..end]
+                .iter()
+                .map(|led| led.position()),
+        )
+    }
+
     /// Modulates the color of each [LED](Led) assigned to the line segment with index `segment_index` given a color rule function. Returns an [error](SledError) if there is no line segment with the given index.
     ///
     /// O(LEDS_IN_SEGMENT)
@@ -39,7 +59,7 @@ impl<Color: ColorType> Sled<Color> {
         segment_index: usize,
         color_rule: F,
     ) -> Result<(), SledError> {
-        if segment_index >= self.line_segment_endpoint_indices.len() {
+        if segment_index >= self.layout.line_segment_endpoint_indices.len() {
             return SledError::new(format!(
                 "Segment of index {} does not exist.",
                 segment_index
@@ -47,10 +67,10 @@ impl<Color: ColorType> Sled<Color> {
             .as_err();
         }
 
-        let (start, end) = self.line_segment_endpoint_indices[segment_index];
-        for led in &mut self.leds[start..end] {
+        let (start, end) = self.layout.line_segment_endpoint_indices[segment_index];
+        self.update_leds(start..end, |led| {
             led.color = color_rule(led);
-        }
+        });
 
         Ok(())
     }
@@ -60,7 +80,7 @@ impl<Color: ColorType> Sled<Color> {
     /// O(LEDS_IN_SEGMENT)
     ///
     pub fn set_segment(&mut self, segment_index: usize, color: Color) -> Result<(), SledError> {
-        if segment_index >= self.line_segment_endpoint_indices.len() {
+        if segment_index >= self.layout.line_segment_endpoint_indices.len() {
             return SledError::new(format!(
                 "No line segment of index {} exists.",
                 segment_index
@@ -68,7 +88,7 @@ impl<Color: ColorType> Sled<Color> {
             .as_err();
         }
 
-        let (start, end) = self.line_segment_endpoint_indices[segment_index];
+        let (start, end) = self.layout.line_segment_endpoint_indices[segment_index];
         self.set_range(start..end, color)?;
         Ok(())
     }
@@ -90,13 +110,12 @@ impl<Color: ColorType> Sled<Color> {
     ///# }
     /// ```
     pub fn segments(&self, range: Range<usize>) -> Option<Filter> {
-        if range.start >= self.line_segment_endpoint_indices.len() {
+        if range.start >= self.layout.line_segment_endpoint_indices.len() {
             None
         } else {
-            let (start, _) = *self.line_segment_endpoint_indices.get(range.start)?;
-            let (_, end) = *self.line_segment_endpoint_indices.get(range.end)?;
-            let led_range = &self.leds[start..end];
-            Some(led_range.into())
+            let (start, _) = *self.layout.line_segment_endpoint_indices.get(range.start)?;
+            let (_, end) = *self.layout.line_segment_endpoint_indices.get(range.end)?;
+            Some(Filter::from_range(start..end))
         }
     }
 
@@ -119,7 +138,7 @@ impl<Color: ColorType> Sled<Color> {
         range: Range<usize>,
         color_rule: F,
     ) -> Result<(), SledError> {
-        if range.start >= self.line_segment_endpoint_indices.len() {
+        if range.start >= self.layout.line_segment_endpoint_indices.len() {
             return SledError::new(
                 "Segment index range extends beyond the number of segments in the system."
                     .to_string(),
@@ -127,11 +146,11 @@ impl<Color: ColorType> Sled<Color> {
             .as_err();
         }
 
-        let (start, _) = self.line_segment_endpoint_indices[range.start];
-        let (_, end) = self.line_segment_endpoint_indices[range.end];
-        for led in &mut self.leds[start..end] {
+        let (start, _) = self.layout.line_segment_endpoint_indices[range.start];
+        let (_, end) = self.layout.line_segment_endpoint_indices[range.end];
+        self.update_leds(start..end, |led| {
             led.color = color_rule(led);
-        }
+        });
         Ok(())
     }
 
@@ -140,7 +159,7 @@ impl<Color: ColorType> Sled<Color> {
     ///
     /// O(LEDS_IN_SEGMENTS)
     pub fn set_segments(&mut self, range: Range<usize>, color: Color) -> Result<(), SledError> {
-        if range.start >= self.line_segment_endpoint_indices.len() {
+        if range.start >= self.layout.line_segment_endpoint_indices.len() {
             return SledError::new(
                 "Segment index range extends beyond the number of segments in the system."
                     .to_string(),
@@ -148,11 +167,11 @@ impl<Color: ColorType> Sled<Color> {
             .as_err();
         }
 
-        let (start, _) = self.line_segment_endpoint_indices[range.start];
-        let (_, end) = self.line_segment_endpoint_indices[range.end];
-        for led in &mut self.leds[start..end] {
+        let (start, _) = self.layout.line_segment_endpoint_indices[range.start];
+        let (_, end) = self.layout.line_segment_endpoint_indices[range.end];
+        self.update_leds(start..end, |led| {
             led.color = color;
-        }
+        });
         Ok(())
     }
 
@@ -177,18 +196,18 @@ impl<Color: ColorType> Sled<Color> {
         segment_index: usize,
         mut func: F,
     ) -> Result<(), SledError> {
-        if segment_index >= self.line_segment_endpoint_indices.len() {
+        if segment_index >= self.layout.line_segment_endpoint_indices.len() {
             return Err(SledError {
                 message: format!("No line segment of index {} exists.", segment_index),
             });
         }
 
-        let (start, end) = self.line_segment_endpoint_indices[segment_index];
+        let (start, end) = self.layout.line_segment_endpoint_indices[segment_index];
         let num_leds_f32 = (end - start) as f32;
 
         for index in start..end {
             let alpha = (index - start) as f32 / num_leds_f32;
-            func(&mut self.leds[index], alpha);
+            self.update_led(index, |led| func(led, alpha));
         }
 
         Ok(())
@@ -202,12 +221,12 @@ impl<Color: ColorType> Sled<Color> {
     ///
     /// O(1)
     ///
-    pub fn vertex(&self, vertex_index: usize) -> Option<&Led<Color>> {
-        if vertex_index >= self.vertex_indices.len() {
+    pub fn vertex(&self, vertex_index: usize) -> Option<Led<'_, Color>> {
+        if vertex_index >= self.layout.vertex_indices.len() {
             return None;
         }
 
-        Some(&self.leds[vertex_index])
+        Some(self.led(vertex_index))
     }
     /// Modulates the color of the [LED](Led) that represents the vertex the given index, if it exists. Returns an [error](SledError) if not.
     /// Vertices are distinct from line segement endpoints in that line segments with touching endpoints will share a vertex.
@@ -231,13 +250,12 @@ impl<Color: ColorType> Sled<Color> {
         vertex_index: usize,
         color_rule: F,
     ) -> Result<(), SledError> {
-        if vertex_index >= self.vertex_indices.len() {
+        if vertex_index >= self.layout.vertex_indices.len() {
             return SledError::new(format!("Vertex of index {} does not exist.", vertex_index))
                 .as_err();
         }
 
-        let led = &mut self.leds[vertex_index];
-        led.color = color_rule(led);
+        self.update_led(vertex_index, |led| led.color = color_rule(led));
         Ok(())
     }
 
@@ -249,7 +267,7 @@ impl<Color: ColorType> Sled<Color> {
     /// O(1)
     ///
     pub fn set_vertex(&mut self, vertex_index: usize, color: Color) -> Result<(), SledError> {
-        if vertex_index >= self.vertex_indices.len() {
+        if vertex_index >= self.layout.vertex_indices.len() {
             return SledError::new(format!(
                 "Vertex with index {} does not exist.",
                 vertex_index
@@ -257,14 +275,15 @@ impl<Color: ColorType> Sled<Color> {
             .as_err();
         }
 
-        self.leds[self.vertex_indices[vertex_index]].color = color;
+        self.colors[self.layout.vertex_indices[vertex_index]] = color;
         Ok(())
     }
 
     /// Returns a [Filter] containing all vertices in the system.
     pub fn vertices(&self) -> Filter {
-        let hs: BTreeSet<u16> = self.vertex_indices.iter().map(|i| *i as u16).collect();
-        hs.into()
+        let mut vertices = Filter::with_capacity(self.num_leds());
+        vertices.extend(self.layout.vertex_indices.iter().map(|i| *i as u16));
+        vertices
     }
 
     /// Modulates the color of each [LED](Led) that represents a vertex in the system.
@@ -283,28 +302,26 @@ impl<Color: ColorType> Sled<Color> {
     ///# }
     /// ```
     pub fn modulate_vertices<F: Fn(&Led<Color>) -> Color>(&mut self, color_rule: F) {
-        for i in &self.vertex_indices {
-            let led = &mut self.leds[*i];
-            led.color = color_rule(led);
-        }
+        let layout = Arc::clone(&self.layout);
+        self.update_leds(layout.vertex_indices.iter().copied(), |led| {
+            led.color = color_rule(led)
+        });
     }
 
     /// Sets the color of each [LED](Led) that represents a vertex in the system.
     ///
     /// O(VERTICES)
     pub fn set_vertices(&mut self, color: Color) {
-        for i in &self.vertex_indices {
-            let led = &mut self.leds[*i];
-            led.color = color;
+        for i in &self.layout.vertex_indices {
+            self.colors[*i] = color;
         }
     }
 
     /// For each method that grants mutable access to each [LED](Led) that represents a vertex in the system.
     ///
     /// O(VERTICES)
-    pub fn for_each_vertex<F: FnMut(&mut Led<Color>)>(&mut self, mut f: F) {
-        for i in &self.vertex_indices {
-            f(&mut self.leds[*i])
-        }
+    pub fn for_each_vertex<F: FnMut(&mut Led<Color>)>(&mut self, f: F) {
+        let layout = Arc::clone(&self.layout);
+        self.update_leds(layout.vertex_indices.iter().copied(), f);
     }
 }
@@ -0,0 +1,421 @@
+use alloc::format;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use smallvec::{smallvec, SmallVec};
+
+use crate::{
+    color::ColorType,
+    config::LineSegment,
+    error::SledError,
+    led::Led,
+    spatial_led::{Filter, Sled},
+    Vec2,
+};
+
+/// Segment endpoints closer than this are treated as the same vertex.
+const COINCIDENCE_TOLERANCE: f32 = 1e-4;
+
+#[derive(Clone, Debug)]
+/// A point in the layout where the endpoints of one or more line segments meet.
+///
+/// Endpoints are merged whenever they are geometrically coincident, even if they belong to separate chains in the config file.
+pub struct Junction {
+    index: usize,
+    position: Vec2,
+    segments: SmallVec<[usize; 4]>,
+    led_indices: SmallVec<[u16; 4]>,
+}
+
+impl Junction {
+    /// Returns the index of this Junction, matching its position in [Sled::endpoints()].
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the position of the Junction in world space.
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    /// Returns the indices of each line segment that starts or ends at this Junction.
+    pub fn segments(&self) -> &[usize] {
+        &self.segments
+    }
+
+    /// Returns the index of the [LED](Led) closest to this Junction on each line segment that touches it.
+    ///
+    /// Segments too short to hold any LEDs are skipped.
+    pub fn led_indices(&self) -> &[u16] {
+        &self.led_indices
+    }
+
+    /// Returns the number of segment endpoints that meet at this Junction.
+    ///
+    /// A degree of 1 is a dead end, 2 is a simple corner, and anything higher is a branch.
+    pub fn degree(&self) -> usize {
+        self.segments.len()
+    }
+}
+
+/// Precomputed connectivity of a Sled's line segments and LEDs.
+#[derive(Clone, Debug)]
+pub(crate) struct Topology {
+    /// Every distinct segment endpoint in the layout.
+    pub nodes: Vec<Junction>,
+    /// Start and end node of each segment.
+    pub segment_nodes: Vec<(usize, usize)>,
+    pub segment_neighbors: Vec<SmallVec<[usize; 4]>>,
+    pub led_neighbors: Vec<SmallVec<[u16; 2]>>,
+    pub segment_components: Vec<usize>,
+    pub num_components: usize,
+}
+
+impl Topology {
+    pub fn new(
+        line_segments: &[LineSegment],
+        line_segment_endpoint_indices: &[(usize, usize)],
+        num_leds: usize,
+    ) -> Self {
+        let mut nodes: Vec<Junction> = vec![];
+        let mut segment_nodes = Vec::with_capacity(line_segments.len());
+
+        for (segment_index, segment) in line_segments.iter().enumerate() {
+            let (first_led, end_led) = line_segment_endpoint_indices[segment_index];
+            let has_leds = end_led > first_led;
+
+            let start_node = Topology::attach(
+                &mut nodes,
+                segment.start,
+                segment_index,
+                has_leds.then_some(first_led as u16),
+            );
+            let end_node = Topology::attach(
+                &mut nodes,
+                segment.end,
+                segment_index,
+                has_leds.then(|| (end_led - 1) as u16),
+            );
+            segment_nodes.push((start_node, end_node));
+        }
+
+        let segment_neighbors = segment_nodes
+            .iter()
+            .enumerate()
+            .map(|(segment_index, (start, end))| {
+                let mut neighbors: SmallVec<[usize; 4]> = smallvec![];
+                for node in [*start, *end] {
+                    for other in &nodes[node].segments {
+                        if *other != segment_index && !neighbors.contains(other) {
+                            neighbors.push(*other);
+                        }
+                    }
+                }
+                neighbors
+            })
+            .collect();
+
+        let mut led_neighbors: Vec<SmallVec<[u16; 2]>> = vec![smallvec![]; num_leds];
+        for (start, end) in line_segment_endpoint_indices {
+            for i in (*start + 1)..*end {
+                led_neighbors[i - 1].push(i as u16);
+                led_neighbors[i].push((i - 1) as u16);
+            }
+        }
+
+        for node in &nodes {
+            for a in &node.led_indices {
+                for b in &node.led_indices {
+                    let neighbors = &mut led_neighbors[*a as usize];
+                    if a != b && !neighbors.contains(b) {
+                        neighbors.push(*b);
+                    }
+                }
+            }
+        }
+
+        let (segment_components, num_components) =
+            Topology::segment_components(&segment_nodes, nodes.len());
+
+        Topology {
+            nodes,
+            segment_nodes,
+            segment_neighbors,
+            led_neighbors,
+            segment_components,
+            num_components,
+        }
+    }
+
+    /// Registers a segment endpoint with the node at `position`, creating that node if needed. Returns the node's index.
+    fn attach(
+        nodes: &mut Vec<Junction>,
+        position: Vec2,
+        segment_index: usize,
+        led_index: Option<u16>,
+    ) -> usize {
+        let node_index = match nodes
+            .iter()
+            .position(|node| node.position.distance(position) < COINCIDENCE_TOLERANCE)
+        {
+            Some(i) => i,
+            None => {
+                nodes.push(Junction {
+                    index: nodes.len(),
+                    position,
+                    segments: smallvec![],
+                    led_indices: smallvec![],
+                });
+                nodes.len() - 1
+            }
+        };
+
+        let node = &mut nodes[node_index];
+        node.segments.push(segment_index);
+        if let Some(led_index) = led_index {
+            if !node.led_indices.contains(&led_index) {
+                node.led_indices.push(led_index);
+            }
+        }
+
+        node_index
+    }
+
+    fn segment_components(
+        segment_nodes: &[(usize, usize)],
+        num_nodes: usize,
+    ) -> (Vec<usize>, usize) {
+        // union-find over nodes; segments join their two endpoints.
+        let mut parents: Vec<usize> = (0..num_nodes).collect();
+
+        fn root(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+
+        for (start, end) in segment_nodes {
+            let a = root(&mut parents, *start);
+            let b = root(&mut parents, *end);
+            parents[a] = b;
+        }
+
+        let mut root_to_component: Vec<Option<usize>> = vec![None; num_nodes];
+        let mut num_components = 0;
+        let segment_components = segment_nodes
+            .iter()
+            .map(|(start, _)| {
+                let r = root(&mut parents, *start);
+                *root_to_component[r].get_or_insert_with(|| {
+                    num_components += 1;
+                    num_components - 1
+                })
+            })
+            .collect();
+
+        (segment_components, num_components)
+    }
+}
+
+/// # Topology-based read and write methods.
+impl<Color: ColorType> Sled<Color> {
+    /// Returns an Iterator over the indices of each [LED](Led) directly connected to the LED at `index`.
+    ///
+    /// Neighbors include the LEDs on either side along the strip, as well as the LEDs on the far side of any [Junction] this LED sits at, even if they belong to a different chain.
+    /// If no LED exists at `index`, the Iterator will be empty.
+    ///
+    /// O(1)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled};
+    ///# use palette::rgb::Rgb;
+    ///# let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// for neighbor in sled.neighbor_indices(0) {
+    ///     println!("LED 0 touches LED {}", neighbor);
+    /// }
+    /// ```
+    pub fn neighbor_indices(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.layout
+            .topology
+            .led_neighbors
+            .get(index)
+            .into_iter()
+            .flatten()
+            .map(|i| *i as usize)
+    }
+
+    /// Returns an Iterator over each [LED](Led) directly connected to the LED at `index`. See [Sled::neighbor_indices()].
+    ///
+    /// O(1)
+    pub fn neighbors(&self, index: usize) -> impl Iterator<Item = Led<'_, Color>> {
+        self.neighbor_indices(index).map(|i| self.led(i))
+    }
+
+    /// Modulates the color of each [LED](Led) directly connected to the LED at `index`. See [Sled::neighbor_indices()].
+    /// Returns an [error](SledError) if no LED exists at that index.
+    ///
+    /// O(1)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, SledError};
+    ///# use palette::rgb::Rgb;
+    ///# fn demo() -> Result<(), SledError> {
+    ///# let mut sled = Sled::<Rgb>::new("./benches/config.yap")?;
+    /// sled.modulate_neighbors(30, |led| led.color * 0.5)?;
+    ///# Ok(())
+    ///# }
+    /// ```
+    pub fn modulate_neighbors<F: Fn(&Led<Color>) -> Color>(
+        &mut self,
+        index: usize,
+        color_rule: F,
+    ) -> Result<(), SledError> {
+        if index >= self.layout.num_leds {
+            return SledError::new(format!("LED at index {} does not exist.", index)).as_err();
+        }
+
+        let layout = Arc::clone(&self.layout);
+        let neighbors = layout.topology.led_neighbors[index].iter();
+        self.update_leds(neighbors.map(|i| *i as usize), |led| {
+            led.color = color_rule(led)
+        });
+
+        Ok(())
+    }
+
+    /// Sets the color of each [LED](Led) directly connected to the LED at `index`. See [Sled::neighbor_indices()].
+    /// Returns an [error](SledError) if no LED exists at that index.
+    ///
+    /// O(1)
+    pub fn set_neighbors(&mut self, index: usize, color: Color) -> Result<(), SledError> {
+        if index >= self.layout.num_leds {
+            return SledError::new(format!("LED at index {} does not exist.", index)).as_err();
+        }
+
+        for neighbor in &self.layout.topology.led_neighbors[index] {
+            self.colors[*neighbor as usize] = color;
+        }
+
+        Ok(())
+    }
+
+    /// Returns an Iterator over the indices of each line segment that shares an endpoint with the segment at `segment_index`.
+    /// If no segment exists at that index, the Iterator will be empty.
+    ///
+    /// O(1)
+    pub fn adjacent_segments(&self, segment_index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.layout
+            .topology
+            .segment_neighbors
+            .get(segment_index)
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    /// Returns the pair of [Junctions](Junction) at the start and end of the line segment with index `segment_index`, if it exists.
+    ///
+    /// O(1)
+    pub fn segment_endpoints(&self, segment_index: usize) -> Option<(&Junction, &Junction)> {
+        let (start, end) = *self.layout.topology.segment_nodes.get(segment_index)?;
+        Some((
+            &self.layout.topology.nodes[start],
+            &self.layout.topology.nodes[end],
+        ))
+    }
+
+    /// Returns an Iterator over every [Junction] where three or more segment endpoints meet.
+    ///
+    /// O(VERTICES)
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled};
+    ///# use palette::rgb::Rgb;
+    ///# let sled = Sled::<Rgb>::new("./benches/config.yap").unwrap();
+    /// for junction in sled.junctions() {
+    ///     println!("{} segments meet at {}", junction.degree(), junction.position());
+    /// }
+    /// ```
+    ///
+    /// Endpoints from different chains are joined too. Here, a second chain forms a T-junction with the first:
+    ///
+    /// ```rust
+    ///# use spatial_led::{Sled, Vec2};
+    /// let sled = Sled::<f32>::new_from_str(
+    ///     "center: (0, 0)
+    ///     density: 10
+    ///     --segments--
+    ///     (-1, 0) --> (0, 0) --> (1, 0) |
+    ///     (0, -1) --> (0, 0)",
+    /// )
+    /// .unwrap();
+    ///
+    /// let junctions: Vec<_> = sled.junctions().collect();
+    /// assert_eq!(junctions.len(), 1);
+    /// assert_eq!(junctions[0].position(), Vec2::ZERO);
+    /// assert_eq!(junctions[0].degree(), 3);
+    ///
+    /// // the stem (segment 2) connects to both halves of the other chain
+    /// assert_eq!(sled.adjacent_segments(2).collect::<Vec<_>>(), [0, 1]);
+    /// assert_eq!(sled.adjacent_segments(0).collect::<Vec<_>>(), [1, 2]);
+    /// // as does the stem's last LED
+    /// let mut neighbors: Vec<_> = sled.neighbor_indices(29).collect();
+    /// neighbors.sort();
+    /// assert_eq!(neighbors, [9, 10, 28]);
+    /// ```
+    pub fn junctions(&self) -> impl Iterator<Item = &Junction> {
+        self.layout
+            .topology
+            .nodes
+            .iter()
+            .filter(|node| node.degree() > 2)
+    }
+
+    /// Returns an Iterator over every distinct segment endpoint in the system, including corners and dead ends.
+    ///
+    /// O(VERTICES)
+    pub fn endpoints(&self) -> impl Iterator<Item = &Junction> {
+        self.layout.topology.nodes.iter()
+    }
+
+    /// Returns the total number of connected components in the system.
+    ///
+    /// Two line segments belong to the same component if you can travel from one to the other along the strip, crossing any junctions on the way.
+    ///
+    /// O(1)
+    pub fn num_components(&self) -> usize {
+        self.layout.topology.num_components
+    }
+
+    /// Returns the index of the connected component that the [LED](Led) at `index` belongs to, if the LED exists.
+    ///
+    /// O(1)
+    pub fn component_of(&self, index: usize) -> Option<usize> {
+        let led = self.layout.leds.get(index)?;
+        Some(self.layout.topology.segment_components[led.segment() as usize])
+    }
+
+    /// Returns the set of all [LEDs](Led) in the connected component with index `component_index`, if it exists.
+    ///
+    /// O(LEDS_IN_COMPONENT)
+    pub fn component(&self, component_index: usize) -> Option<Filter> {
+        if component_index >= self.layout.topology.num_components {
+            return None;
+        }
+
+        let filter = self
+            .layout
+            .topology
+            .segment_components
+            .iter()
+            .zip(&self.layout.line_segment_endpoint_indices)
+            .filter(|(component, _)| **component == component_index)
+            .flat_map(|(_, (start, end))| *start as u16..*end as u16)
+            .collect();
+
+        Some(filter)
+    }
+}